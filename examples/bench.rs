@@ -0,0 +1,61 @@
+// Copyright 2015, Paul Osborne <osbpau@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::env;
+use std::time::Duration;
+use sysfs_gpio::bench::{measure_latency, measure_syscalls, measure_toggle_rate};
+use sysfs_gpio::{Direction, Pin};
+
+const ITERATIONS: usize = 10_000;
+
+fn bench(output: u64, input: Option<u64>) -> sysfs_gpio::Result<()> {
+    let out = Pin::new(output);
+    out.with_exported(|| {
+        out.set_direction(Direction::Low)?;
+        let handle = out.get_handle()?;
+
+        let toggle = measure_toggle_rate(&handle, ITERATIONS as u64)?;
+        println!(
+            "toggle: {} writes in {:?} ({:.0} Hz)",
+            toggle.toggles,
+            toggle.elapsed,
+            toggle.frequency()
+        );
+
+        let syscalls = measure_syscalls(&handle, ITERATIONS)?;
+        println!("write:  {}", syscalls.write);
+        println!("read:   {}", syscalls.read);
+
+        if let Some(input) = input {
+            let inp = Pin::new(input);
+            inp.with_exported(|| {
+                inp.set_direction(Direction::In)?;
+                let latency =
+                    measure_latency(&handle, inp, ITERATIONS, Duration::from_millis(100))?;
+                println!("latency: {}", latency.latency);
+                println!("missed: {} of {} edges", latency.missed, latency.sent);
+                Ok(())
+            })?;
+        }
+        Ok(())
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let pins: Result<Vec<u64>, _> = args[1..].iter().map(|a| a.parse::<u64>()).collect();
+    match pins.as_deref() {
+        Ok([output]) => bench(*output, None),
+        Ok([output, input]) => bench(*output, Some(*input)),
+        _ => {
+            println!("Usage: ./bench <output_pin> [<loopback_input_pin>]");
+            return;
+        }
+    }
+    .unwrap_or_else(|err| println!("Error: {}", err));
+}
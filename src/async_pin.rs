@@ -0,0 +1,201 @@
+use std::io;
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use crate::{Direction, Edge, Error, Level, Pin, PinStream, PinValueStream, Result};
+
+/// A Pin whose operations do not block the async executor
+///
+/// Every attribute access on a `Pin` is blocking file I/O, and
+/// `export` in particular can take a long time while udev sets up the
+/// exported pin.  The methods of `AsyncPin` run the corresponding
+/// `Pin` method on tokio's blocking thread pool instead, so they must
+/// be called from within a tokio runtime.
+///
+/// This type is only available when the `async-tokio` crate feature
+/// is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{AsyncPin, Direction, Level, Pin};
+///
+/// # async fn run() -> sysfs_gpio::Result<()> {
+/// let led = AsyncPin::new(Pin::new(127));
+/// led.export().await?;
+/// led.set_direction(Direction::Low).await?;
+/// led.pulse(Level::High, Duration::from_millis(100)).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AsyncPin {
+    pin: Pin,
+}
+
+impl AsyncPin {
+    /// Wrap a Pin
+    pub fn new(pin: Pin) -> AsyncPin {
+        AsyncPin { pin }
+    }
+
+    /// Get the underlying Pin
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Run `f` with the pin on the blocking thread pool
+    async fn blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(Pin) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pin = self.pin;
+        tokio::task::spawn_blocking(move || f(pin))
+            .await
+            .map_err(|err| Error::Io(io::Error::other(err)))?
+    }
+
+    /// Determines whether the GPIO is exported
+    pub async fn is_exported(&self) -> Result<bool> {
+        self.blocking(|pin| Ok(pin.is_exported())).await
+    }
+
+    /// Export the GPIO; see `Pin::export`
+    pub async fn export(&self) -> Result<()> {
+        self.blocking(|pin| pin.export()).await
+    }
+
+    /// Unexport the GPIO; see `Pin::unexport`
+    pub async fn unexport(&self) -> Result<()> {
+        self.blocking(|pin| pin.unexport()).await
+    }
+
+    /// Get the direction of the Pin
+    pub async fn get_direction(&self) -> Result<Direction> {
+        self.blocking(|pin| pin.get_direction()).await
+    }
+
+    /// Set the direction of the Pin; see `Pin::set_direction`
+    pub async fn set_direction(&self, dir: Direction) -> Result<()> {
+        self.blocking(move |pin| pin.set_direction(dir)).await
+    }
+
+    /// Get the value of the Pin (0 or 1)
+    pub async fn get_value(&self) -> Result<u8> {
+        self.blocking(|pin| pin.get_value()).await
+    }
+
+    /// Set the value of the Pin; see `Pin::set_value`
+    pub async fn set_value(&self, value: u8) -> Result<()> {
+        self.blocking(move |pin| pin.set_value(value)).await
+    }
+
+    /// Get the currently configured edge for this pin
+    pub async fn get_edge(&self) -> Result<Edge> {
+        self.blocking(|pin| pin.get_edge()).await
+    }
+
+    /// Set the edge on which this GPIO will trigger; see `Pin::set_edge`
+    pub async fn set_edge(&self, edge: Edge) -> Result<()> {
+        self.blocking(move |pin| pin.set_edge(edge)).await
+    }
+
+    /// Get polarity of the Pin (`true` is active low)
+    pub async fn get_active_low(&self) -> Result<bool> {
+        self.blocking(|pin| pin.get_active_low()).await
+    }
+
+    /// Set the polarity of the Pin (`true` is active low)
+    pub async fn set_active_low(&self, active_low: bool) -> Result<()> {
+        self.blocking(move |pin| pin.set_active_low(active_low))
+            .await
+    }
+
+    /// Wait until the pin is at the provided level
+    ///
+    /// This is the async counterpart of `Pin::wait_for_level`: the
+    /// pin's edge is temporarily configured while waiting and restored
+    /// afterwards, and the current level is only checked once the
+    /// interrupt has been set up.  If the future is dropped before it
+    /// completes, the edge is restored on the blocking thread pool.
+    ///
+    /// Returns `true` once the pin is at `level`, or `false` if
+    /// `timeout` expired first.  A `timeout` of `None` waits forever.
+    pub async fn wait_for_level(&self, level: Level, timeout: Option<Duration>) -> Result<bool> {
+        let previous = self.get_edge().await?;
+        self.set_edge(match level {
+            Level::Low => Edge::FallingEdge,
+            Level::High => Edge::RisingEdge,
+        })
+        .await?;
+        // restores the edge in the background if this future is dropped
+        let mut guard = EdgeGuard {
+            pin: self.pin,
+            previous: Some(previous),
+        };
+        let result = self.wait_for_level_inner(level, timeout).await;
+        guard.previous = None;
+        let restored = self.set_edge(previous).await;
+        let reached = result?;
+        restored?;
+        Ok(reached)
+    }
+
+    async fn wait_for_level_inner(&self, level: Level, timeout: Option<Duration>) -> Result<bool> {
+        // The first readiness event is delivered rather than skipped so
+        // that a change racing with setting up the stream is not lost;
+        // a spurious wake-up only causes the level to be checked again.
+        let poller = self.blocking(|pin| pin.get_async_poller()).await?;
+        let mut stream = PinValueStream(PinStream::init_with_first_event(poller)?);
+        if Level::from(self.get_value().await?) == level {
+            return Ok(true);
+        }
+        let wait = async {
+            while let Some(value) = stream.next().await {
+                if Level::from(value?) == level {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait)
+                .await
+                .unwrap_or(Ok(false)),
+            None => wait.await,
+        }
+    }
+
+    /// Hold the pin at `level` for `duration`, then restore its previous value
+    pub async fn pulse(&self, level: Level, duration: Duration) -> Result<()> {
+        let previous = self.get_value().await?;
+        self.set_value(level.into()).await?;
+        tokio::time::sleep(duration).await;
+        self.set_value(previous).await
+    }
+}
+
+/// Restores a pin's edge when a `wait_for_level` future is dropped early
+struct EdgeGuard {
+    pin: Pin,
+    previous: Option<Edge>,
+}
+
+impl Drop for EdgeGuard {
+    fn drop(&mut self) {
+        if let Some(edge) = self.previous.take() {
+            let pin = self.pin;
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    handle.spawn_blocking(move || pin.set_edge(edge));
+                }
+                Err(_) => {
+                    let _ = pin.set_edge(edge);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Measuring what the sysfs interface achieves on a board
+//!
+//! Before committing to bit-banging a protocol over sysfs it helps to
+//! know how fast a pin can be toggled and how long it takes for a
+//! write to one pin to be seen as an interrupt on another.  The
+//! functions in this module measure this given an output pin looped
+//! back to an input pin, or only the cost of the value system calls
+//! when no loopback is available.  The `bench` example wraps them in
+//! a command line tool.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::{Edge, Level, Pin, PinHandle, Result};
+
+/// Summary of a set of measured durations
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The samples in ascending order
+    sorted: Vec<Duration>,
+}
+
+impl Stats {
+    /// Summarize the provided samples
+    pub fn new(mut samples: Vec<Duration>) -> Stats {
+        samples.sort();
+        Stats { sorted: samples }
+    }
+
+    /// Get the number of samples
+    pub fn count(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// Get the smallest sample
+    pub fn min(&self) -> Option<Duration> {
+        self.sorted.first().copied()
+    }
+
+    /// Get the largest sample
+    pub fn max(&self) -> Option<Duration> {
+        self.sorted.last().copied()
+    }
+
+    /// Get the mean of the samples
+    pub fn mean(&self) -> Option<Duration> {
+        let total: Duration = self.sorted.iter().sum();
+        u32::try_from(self.sorted.len())
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| total / n)
+    }
+
+    /// Get the `p`th percentile (0 to 100) using the nearest-rank method
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.sorted.is_empty() {
+            return None;
+        }
+        let p = p.clamp(0.0, 100.0);
+        let rank = (p / 100.0 * self.sorted.len() as f64).ceil() as usize;
+        Some(self.sorted[rank.saturating_sub(1)])
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sorted.is_empty() {
+            return write!(f, "no samples");
+        }
+        write!(f, "n={}", self.count())?;
+        for &(name, p) in &[("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("p99.9", 99.9)] {
+            write!(f, " {}={:?}", name, self.percentile(p).unwrap_or_default())?;
+        }
+        write!(f, " max={:?}", self.max().unwrap_or_default())
+    }
+}
+
+/// Result of `measure_toggle_rate`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToggleReport {
+    /// Number of writes made to the pin
+    pub toggles: u64,
+    /// Time taken for all writes
+    pub elapsed: Duration,
+}
+
+impl ToggleReport {
+    /// Get the frequency of the resulting square wave in Hz
+    pub fn frequency(&self) -> f64 {
+        self.toggles as f64 / 2.0 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Result of `measure_latency`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// Time from writing the output to the interrupt being handled
+    pub latency: Stats,
+    /// Number of edges written to the output
+    pub sent: u64,
+    /// Number of edges for which no matching interrupt was seen
+    pub missed: u64,
+}
+
+/// Result of `measure_syscalls`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyscallReport {
+    /// Time taken by each write of the value
+    pub write: Stats,
+    /// Time taken by each read of the value
+    pub read: Stats,
+}
+
+/// Toggle an output as fast as possible `toggles` times
+pub fn measure_toggle_rate(output: &PinHandle, toggles: u64) -> Result<ToggleReport> {
+    let start = Instant::now();
+    for i in 0..toggles {
+        output.set_value((i & 1) as u8)?;
+    }
+    Ok(ToggleReport {
+        toggles,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Measure the time from writing `output` to handling the interrupt on `input`
+///
+/// `output` must be wired to `input`, which must be exported and
+/// configured as an input; its edge is set to `Edge::BothEdges`.  For
+/// each sample the output is inverted and the interrupt waited for for
+/// at most `timeout`.  A sample counts as missed if no interrupt
+/// arrives in time or the input does not read back the level written.
+pub fn measure_latency(
+    output: &PinHandle,
+    input: Pin,
+    samples: usize,
+    timeout: Duration,
+) -> Result<LatencyReport> {
+    input.set_edge(Edge::BothEdges)?;
+    let mut poller = input.get_poller()?;
+    let mut level = Level::from(output.get_value()?);
+    let mut latencies = Vec::with_capacity(samples);
+    let mut missed = 0;
+    for _ in 0..samples {
+        poller.read_value()?;
+        level = !level;
+        let start = Instant::now();
+        output.set_value(level.into())?;
+        let seen = poller.wait_for_event(Some(start + timeout))?;
+        let elapsed = start.elapsed();
+        if seen && Level::from(poller.read_value()?) == level {
+            latencies.push(elapsed);
+        } else {
+            missed += 1;
+        }
+    }
+    Ok(LatencyReport {
+        latency: Stats::new(latencies),
+        sent: samples as u64,
+        missed,
+    })
+}
+
+/// Measure the cost of writing and reading the value of `pin` without a loopback
+///
+/// The pin is written `iterations` times, alternating between 0 and 1,
+/// and read `iterations` times.
+pub fn measure_syscalls(pin: &PinHandle, iterations: usize) -> Result<SyscallReport> {
+    let mut writes = Vec::with_capacity(iterations);
+    let mut reads = Vec::with_capacity(iterations);
+    for i in 0..iterations {
+        let start = Instant::now();
+        pin.set_value((i & 1) as u8)?;
+        writes.push(start.elapsed());
+    }
+    for _ in 0..iterations {
+        let start = Instant::now();
+        pin.get_value()?;
+        reads.push(start.elapsed());
+    }
+    Ok(SyscallReport {
+        write: Stats::new(writes),
+        read: Stats::new(reads),
+    })
+}
+
+#[test]
+fn stats_test() {
+    let us = Duration::from_micros;
+    let stats = Stats::new((1..=100).rev().map(us).collect());
+    assert_eq!(100, stats.count());
+    assert_eq!(Some(us(1)), stats.min());
+    assert_eq!(Some(us(100)), stats.max());
+    assert_eq!(Some(Duration::from_nanos(50_500)), stats.mean());
+    assert_eq!(Some(us(50)), stats.percentile(50.0));
+    assert_eq!(Some(us(99)), stats.percentile(99.0));
+    assert_eq!(Some(us(100)), stats.percentile(99.9));
+    assert_eq!(Some(us(1)), stats.percentile(0.0));
+    assert_eq!(None, Stats::default().percentile(50.0));
+}
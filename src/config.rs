@@ -0,0 +1,230 @@
+use std::fmt;
+
+use crate::{Direction, Edge, Error, Pin, Result};
+
+/// A step performed while applying a `PinConfig`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigStep {
+    Export,
+    ActiveLow,
+    Direction,
+    Edge,
+}
+
+impl fmt::Display for ConfigStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ConfigStep::Export => "export",
+            ConfigStep::ActiveLow => "active_low",
+            ConfigStep::Direction => "direction",
+            ConfigStep::Edge => "edge",
+        })
+    }
+}
+
+/// Builder for the full configuration of a Pin
+///
+/// Only the attributes which have been set on the builder are
+/// written when the configuration is applied.  They are applied in
+/// the order export, active_low, direction and edge.
+///
+/// # Example
+///
+/// ```no_run
+/// use sysfs_gpio::{Direction, Edge, Pin, PinConfig};
+///
+/// let button = Pin::new(23);
+/// PinConfig::new()
+///     .active_low(true)
+///     .direction(Direction::In)
+///     .edge(Edge::BothEdges)
+///     .apply(&button)
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PinConfig {
+    active_low: Option<bool>,
+    direction: Option<Direction>,
+    edge: Option<Edge>,
+}
+
+/// The attributes of a pin as they were before a configuration was applied
+///
+/// Each attribute is saved before it is written, so that a write
+/// which took effect but failed verification is still rolled back.
+#[derive(Default)]
+struct Snapshot {
+    active_low: Option<bool>,
+    direction: Option<Direction>,
+    edge: Option<Edge>,
+}
+
+impl PinConfig {
+    /// Create an empty configuration which only exports the pin
+    pub fn new() -> PinConfig {
+        PinConfig::default()
+    }
+
+    /// Set the polarity of the pin (`true` is active low)
+    pub fn active_low(mut self, active_low: bool) -> PinConfig {
+        self.active_low = Some(active_low);
+        self
+    }
+
+    /// Set the direction of the pin
+    pub fn direction(mut self, dir: Direction) -> PinConfig {
+        self.direction = Some(dir);
+        self
+    }
+
+    /// Set the edge on which the pin will trigger when polled
+    pub fn edge(mut self, edge: Edge) -> PinConfig {
+        self.edge = Some(edge);
+        self
+    }
+
+    /// Export the pin and apply this configuration to it
+    ///
+    /// If any step fails, the attributes which were already written
+    /// are restored to their previous values (and the pin is
+    /// unexported again if this call exported it) before an
+    /// `Error::ConfigFailed` naming the failed step is returned.
+    /// Restoring the previous state is best effort; errors hit while
+    /// rolling back are not reported.
+    pub fn apply(&self, pin: &Pin) -> Result<()> {
+        let was_exported = pin.is_exported();
+        if !was_exported {
+            pin.export().map_err(|e| failed(ConfigStep::Export, e))?;
+        }
+
+        let mut previous = Snapshot::default();
+        match self.apply_attributes(pin, &mut previous) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                if was_exported {
+                    previous.restore(pin);
+                } else {
+                    let _ = pin.unexport();
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn apply_attributes(&self, pin: &Pin, previous: &mut Snapshot) -> Result<()> {
+        if let Some(active_low) = self.active_low {
+            let step = ConfigStep::ActiveLow;
+            previous.active_low = Some(pin.get_active_low().map_err(|e| failed(step, e))?);
+            pin.set_active_low(active_low)
+                .map_err(|e| failed(step, e))?;
+        }
+        if let Some(dir) = self.direction {
+            let step = ConfigStep::Direction;
+            previous.direction = Some(saved_direction(pin).map_err(|e| failed(step, e))?);
+            pin.set_direction(dir).map_err(|e| failed(step, e))?;
+        }
+        if let Some(edge) = self.edge {
+            let step = ConfigStep::Edge;
+            previous.edge = Some(pin.get_edge().map_err(|e| failed(step, e))?);
+            pin.set_edge(edge).map_err(|e| failed(step, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Snapshot {
+    /// Write back the saved attributes in the reverse order they were applied
+    fn restore(&self, pin: &Pin) {
+        if let Some(edge) = self.edge {
+            let _ = pin.set_edge(edge);
+        }
+        if let Some(dir) = self.direction {
+            let _ = pin.set_direction(dir);
+        }
+        if let Some(active_low) = self.active_low {
+            let _ = pin.set_active_low(active_low);
+        }
+    }
+}
+
+/// Get the direction of a pin in a form which restores its output level
+///
+/// sysfs reports any output as "out", and writing "out" back drives
+/// the pin low, so outputs are saved as `Direction::High` or
+/// `Direction::Low` according to their physical level.
+fn saved_direction(pin: &Pin) -> Result<Direction> {
+    match pin.get_direction()? {
+        Direction::Out => {
+            let physical = pin.get_value()? ^ pin.get_active_low()? as u8;
+            Ok(match physical {
+                0 => Direction::Low,
+                _ => Direction::High,
+            })
+        }
+        dir => Ok(dir),
+    }
+}
+
+fn failed(step: ConfigStep, error: Error) -> Error {
+    Error::ConfigFailed {
+        step,
+        error: Box::new(error),
+    }
+}
+
+#[test]
+fn config_step_display_test() {
+    for (step, name) in [
+        (ConfigStep::Export, "export"),
+        (ConfigStep::ActiveLow, "active_low"),
+        (ConfigStep::Direction, "direction"),
+        (ConfigStep::Edge, "edge"),
+    ] {
+        assert_eq!(name, step.to_string());
+    }
+}
+
+#[test]
+fn config_failed_test() {
+    use std::io;
+
+    let err = failed(
+        ConfigStep::Direction,
+        Error::Io(io::Error::from(io::ErrorKind::PermissionDenied)),
+    );
+    match err {
+        Error::ConfigFailed {
+            step: ConfigStep::Direction,
+            error: ref inner,
+        } => assert!(matches!(**inner, Error::Io(_))),
+        ref other => panic!("unexpected error {:?}", other),
+    }
+    assert!(err
+        .to_string()
+        .starts_with("Configuration failed at direction step: "));
+    let io_err = io::Error::from(err);
+    assert_eq!(io::ErrorKind::PermissionDenied, io_err.kind());
+    assert!(io_err.to_string().starts_with("direction step: "));
+
+    let err = failed(ConfigStep::Edge, Error::InvalidArgument("edge".to_owned()));
+    assert_eq!(io::ErrorKind::InvalidInput, io::Error::from(err).kind());
+}
+
+#[test]
+fn config_rollback_test() {
+    // no such pin can be exported, so the export step fails and the
+    // rollback leaves the pin unexported
+    let pin = Pin::new(u64::MAX);
+    let err = PinConfig::new()
+        .direction(Direction::In)
+        .apply(&pin)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ConfigFailed {
+            step: ConfigStep::Export,
+            ..
+        }
+    ));
+    assert!(!pin.is_exported());
+}
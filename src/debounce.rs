@@ -0,0 +1,315 @@
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "async-tokio")]
+use futures::Stream;
+#[cfg(feature = "async-tokio")]
+use std::future::Future;
+#[cfg(feature = "async-tokio")]
+use std::task::{Context, Poll};
+
+#[cfg(not(target_os = "wasi"))]
+use crate::PinPoller;
+#[cfg(feature = "async-tokio")]
+use crate::PinValueStream;
+use crate::{Level, Result};
+
+/// How long a pin must hold a new level before the change is reported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DebounceConfig {
+    press: Duration,
+    release: Duration,
+}
+
+impl DebounceConfig {
+    /// Require the pin to be stable for `stable` after any change
+    pub fn new(stable: Duration) -> DebounceConfig {
+        DebounceConfig {
+            press: stable,
+            release: stable,
+        }
+    }
+
+    /// Use separate stable times for changes to high (`press`) and to low (`release`)
+    pub fn asymmetric(press: Duration, release: Duration) -> DebounceConfig {
+        DebounceConfig { press, release }
+    }
+
+    fn stable_time(&self, level: Level) -> Duration {
+        match level {
+            Level::High => self.press,
+            Level::Low => self.release,
+        }
+    }
+}
+
+/// Debouncing state machine
+///
+/// The debouncer is fed raw levels with the time they were observed
+/// and reports a change once the new level has been held for the
+/// configured stable time.  It never reads the clock itself, which
+/// keeps it independent of the event source and lets it be driven by
+/// a simulated clock.  `DebouncedPoller` and `DebouncedStream` wrap it
+/// around the blocking and tokio interfaces.
+#[derive(Clone, Debug)]
+pub struct Debouncer {
+    config: DebounceConfig,
+    level: Level,
+    pending: Option<(Level, Instant)>,
+}
+
+impl Debouncer {
+    /// Create a debouncer whose settled level starts at `initial`
+    pub fn new(config: DebounceConfig, initial: Level) -> Debouncer {
+        Debouncer {
+            config,
+            level: initial,
+            pending: None,
+        }
+    }
+
+    /// Get the last settled level
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Get the time at which the pending change settles, if there is one
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending
+            .map(|(level, since)| since + self.config.stable_time(level))
+    }
+
+    /// Record the raw level observed at `now`
+    ///
+    /// Returns the new settled level if this settles a change.
+    pub fn update(&mut self, level: Level, now: Instant) -> Option<Level> {
+        if level == self.level {
+            self.pending = None;
+        } else if self.pending.is_none_or(|(pending, _)| pending != level) {
+            self.pending = Some((level, now));
+        }
+        self.poll(now)
+    }
+
+    /// Check whether a pending change has settled by `now`
+    ///
+    /// Returns the new settled level if it has.
+    pub fn poll(&mut self, now: Instant) -> Option<Level> {
+        match self.deadline() {
+            Some(deadline) if deadline <= now => {
+                self.level = !self.level;
+                self.pending = None;
+                Some(self.level)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A `PinPoller` which only reports settled level changes
+///
+/// The pin's edge should be set to `Edge::BothEdges` so that bounces
+/// in either direction are seen.
+#[cfg(not(target_os = "wasi"))]
+#[derive(Debug)]
+pub struct DebouncedPoller {
+    poller: PinPoller,
+    debouncer: Debouncer,
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl DebouncedPoller {
+    /// Wrap a poller, taking the current value of the pin as the settled level
+    pub fn new(mut poller: PinPoller, config: DebounceConfig) -> Result<DebouncedPoller> {
+        let initial = Level::from(poller.read_value()?);
+        Ok(DebouncedPoller {
+            poller,
+            debouncer: Debouncer::new(config, initial),
+        })
+    }
+
+    /// Get the last settled level
+    pub fn level(&self) -> Level {
+        self.debouncer.level()
+    }
+
+    /// Block until the level of the pin changes and settles
+    ///
+    /// Returns `Some(level)` with the new level, or `None` if no change
+    /// settled within `timeout_ms` milliseconds.  A negative timeout
+    /// waits forever.
+    pub fn poll(&mut self, timeout_ms: isize) -> Result<Option<Level>> {
+        let timeout_at = u64::try_from(timeout_ms)
+            .ok()
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+        loop {
+            let wake = match (self.debouncer.deadline(), timeout_at) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            let settled = match self.poller.wait_for_event(wake)? {
+                true => {
+                    let level = Level::from(self.poller.read_value()?);
+                    self.debouncer.update(level, Instant::now())
+                }
+                false => self.debouncer.poll(Instant::now()),
+            };
+            if settled.is_some() {
+                return Ok(settled);
+            }
+            // checked after every wake, so continuous bouncing cannot
+            // hold the call past its timeout
+            if timeout_at.is_some_and(|t| t <= Instant::now()) {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// A stream of pin values which only yields settled level changes
+///
+/// This wraps any stream of raw pin values, such as a
+/// `PinValueStream`.  The pin's edge should be set to
+/// `Edge::BothEdges` so that bounces in either direction are seen.
+#[cfg(feature = "async-tokio")]
+pub struct DebouncedStream<S> {
+    inner: S,
+    debouncer: Debouncer,
+    sleep: std::pin::Pin<Box<tokio::time::Sleep>>,
+}
+
+#[cfg(feature = "async-tokio")]
+impl DebouncedStream<PinValueStream> {
+    /// Wrap a `PinValueStream`, taking the current value of the pin as the settled level
+    pub fn from_value_stream(
+        mut stream: PinValueStream,
+        config: DebounceConfig,
+    ) -> Result<DebouncedStream<PinValueStream>> {
+        let initial = Level::from(stream.get_value()?);
+        Ok(DebouncedStream::new(stream, config, initial))
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+impl<S> DebouncedStream<S>
+where
+    S: Stream<Item = Result<u8>> + Unpin,
+{
+    /// Wrap a stream of raw values whose settled level starts at `initial`
+    pub fn new(inner: S, config: DebounceConfig, initial: Level) -> DebouncedStream<S> {
+        DebouncedStream {
+            inner,
+            debouncer: Debouncer::new(config, initial),
+            sleep: Box::pin(tokio::time::sleep(Duration::ZERO)),
+        }
+    }
+
+    /// Get the last settled level
+    pub fn level(&self) -> Level {
+        self.debouncer.level()
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+impl<S> Stream for DebouncedStream<S>
+where
+    S: Stream<Item = Result<u8>> + Unpin,
+{
+    type Item = Result<Level>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            match std::pin::Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(value))) => {
+                    let now = tokio::time::Instant::now().into_std();
+                    if let Some(level) = this.debouncer.update(Level::from(value), now) {
+                        return Poll::Ready(Some(Ok(level)));
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => break,
+            }
+        }
+        if let Some(deadline) = this.debouncer.deadline() {
+            let deadline = tokio::time::Instant::from_std(deadline);
+            if this.sleep.deadline() != deadline {
+                this.sleep.as_mut().reset(deadline);
+            }
+            if this.sleep.as_mut().poll(cx).is_ready() {
+                let now = tokio::time::Instant::now().into_std();
+                if let Some(level) = this.debouncer.poll(now) {
+                    return Poll::Ready(Some(Ok(level)));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[test]
+fn debouncer_test() {
+    let start = Instant::now();
+    let ms = |n| start + Duration::from_millis(n);
+    let mut d = Debouncer::new(DebounceConfig::new(Duration::from_millis(10)), Level::Low);
+
+    // bounces shorter than the stable time are ignored
+    assert_eq!(None, d.update(Level::High, ms(0)));
+    assert_eq!(None, d.update(Level::Low, ms(3)));
+    assert_eq!(None, d.update(Level::High, ms(5)));
+    assert_eq!(Some(ms(15)), d.deadline());
+    assert_eq!(None, d.poll(ms(14)));
+    assert_eq!(Some(Level::High), d.poll(ms(15)));
+    assert_eq!(Level::High, d.level());
+    assert_eq!(None, d.deadline());
+
+    // returning to the settled level cancels the pending change
+    assert_eq!(None, d.update(Level::Low, ms(20)));
+    assert_eq!(None, d.update(Level::High, ms(25)));
+    assert_eq!(None, d.poll(ms(40)));
+
+    // a late event settles immediately
+    assert_eq!(None, d.update(Level::Low, ms(50)));
+    assert_eq!(Some(Level::Low), d.update(Level::Low, ms(70)));
+}
+
+#[test]
+fn debouncer_asymmetric_test() {
+    let start = Instant::now();
+    let ms = |n| start + Duration::from_millis(n);
+    let config = DebounceConfig::asymmetric(Duration::from_millis(5), Duration::from_millis(50));
+    let mut d = Debouncer::new(config, Level::Low);
+
+    assert_eq!(None, d.update(Level::High, ms(0)));
+    assert_eq!(Some(Level::High), d.poll(ms(5)));
+    assert_eq!(None, d.update(Level::Low, ms(10)));
+    assert_eq!(None, d.poll(ms(59)));
+    assert_eq!(Some(Level::Low), d.poll(ms(60)));
+}
+
+#[cfg(all(test, feature = "async-tokio"))]
+#[tokio::test(start_paused = true)]
+async fn debounced_stream_test() {
+    use futures::StreamExt;
+
+    let (tx, rx) = futures::channel::mpsc::unbounded::<Result<u8>>();
+    let config = DebounceConfig::new(Duration::from_millis(10));
+    let mut stream = DebouncedStream::new(rx, config, Level::Low);
+    let start = tokio::time::Instant::now();
+
+    tokio::spawn(async move {
+        for value in [1, 0, 1] {
+            tx.unbounded_send(Ok(value)).unwrap();
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    });
+
+    // the last bounce is at 4ms, so the level settles 10ms later
+    assert_eq!(Level::High, stream.next().await.unwrap().unwrap());
+    assert_eq!(Duration::from_millis(14), start.elapsed());
+    assert!(stream.next().await.is_none());
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use nix::errno::Errno;
+use nix::sys::epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags, EpollTimeout};
+use nix::sys::eventfd::EventFd;
+
+use crate::{get_value_from_file, Edge, Error, Level, Pin, PinEvent, RealtimeConfig, Result};
+
+/// epoll data used for the eventfd which wakes the dispatcher thread
+const WAKE: u64 = u64::MAX;
+
+/// Identifies a registration made with a `Dispatcher`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RegistrationId(u64);
+
+type Callback = Box<dyn FnMut(PinEvent) + Send>;
+
+enum Handler {
+    Callback(Callback),
+    Channel(mpsc::Sender<PinEvent>),
+}
+
+struct Registration {
+    pin: Pin,
+    devfile: File,
+    handler: Arc<Mutex<Handler>>,
+}
+
+struct Shared {
+    epoll: Epoll,
+    wake: EventFd,
+    shutdown: AtomicBool,
+    /// Set, with the registrations lock held, when the thread exits
+    stopped: AtomicBool,
+    next_id: AtomicU64,
+    registrations: Mutex<HashMap<u64, Registration>>,
+}
+
+impl Shared {
+    fn registrations(&self) -> MutexGuard<'_, HashMap<u64, Registration>> {
+        // callbacks run outside this lock, so it is only poisoned if the
+        // map itself panicked, which leaves it consistent
+        self.registrations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn remove(&self, id: u64) -> Result<bool> {
+        let mut registrations = self.registrations();
+        match registrations.remove(&id) {
+            Some(registration) => {
+                self.epoll.delete(&registration.devfile)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Background thread delivering pin interrupts to callbacks or channels
+///
+/// A `Dispatcher` owns a single thread which waits on one epoll
+/// instance for interrupts on every registered pin.  For each
+/// interrupt the value of the pin is read and a `PinEvent` is passed
+/// to the registered callback or sent on the registered channel.
+///
+/// Callbacks run on the dispatcher thread, so they should return
+/// quickly; a callback which blocks delays the delivery of all other
+/// events.  A callback which panics is removed, and the other
+/// registrations keep receiving events.  The thread is shut down when
+/// the `Dispatcher` is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use sysfs_gpio::{Direction, Dispatcher, Edge, Pin};
+///
+/// let button = Pin::new(23);
+/// button.export().unwrap();
+/// button.set_direction(Direction::In).unwrap();
+///
+/// let dispatcher = Dispatcher::new().unwrap();
+/// dispatcher
+///     .on_edge(button, Edge::RisingEdge, |event| println!("{:?}", event))
+///     .unwrap();
+/// ```
+pub struct Dispatcher {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Dispatcher {
+    /// Create a dispatcher and start its thread
+    pub fn new() -> Result<Dispatcher> {
+        Dispatcher::start(None)
+    }
+
+    /// Create a dispatcher whose thread runs with real-time tuning
+    ///
+    /// The configuration is applied by the dispatcher thread before it
+    /// starts waiting for interrupts; if that fails, the thread is
+    /// stopped and the error is returned.
+    pub fn with_realtime(config: RealtimeConfig) -> Result<Dispatcher> {
+        Dispatcher::start(Some(config))
+    }
+
+    fn start(realtime: Option<RealtimeConfig>) -> Result<Dispatcher> {
+        let epoll = Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC)?;
+        let wake = EventFd::new()?;
+        epoll.add(&wake, EpollEvent::new(EpollFlags::EPOLLIN, WAKE))?;
+        let shared = Arc::new(Shared {
+            epoll,
+            wake,
+            shutdown: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            next_id: AtomicU64::new(0),
+            registrations: Mutex::new(HashMap::new()),
+        });
+        let thread_shared = shared.clone();
+        let (started_tx, started_rx) = mpsc::sync_channel(1);
+        let thread = thread::Builder::new()
+            .name("sysfs-gpio-dispatcher".to_owned())
+            .spawn(move || {
+                let started = realtime.map_or(Ok(()), |config| config.apply());
+                let ok = started.is_ok();
+                let _ = started_tx.send(started);
+                let _stopped = StoppedGuard(&thread_shared);
+                if ok {
+                    run(&thread_shared)
+                }
+            })?;
+        let started = started_rx
+            .recv()
+            .unwrap_or_else(|_| Err(Error::Unexpected("dispatcher thread panicked".to_owned())));
+        if let Err(err) = started {
+            let _ = thread.join();
+            return Err(err);
+        }
+        Ok(Dispatcher {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Determines whether the dispatcher thread is still delivering events
+    ///
+    /// The thread only stops on its own if waiting for interrupts
+    /// fails.  Registering with a stopped dispatcher returns an error.
+    pub fn is_running(&self) -> bool {
+        !self.shared.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Call `callback` on the dispatcher thread for each interrupt on `pin`
+    ///
+    /// The pin must be exported and configured as an input.  Its edge
+    /// is set to `edge`, so registering the same pin twice with
+    /// different edges changes the edge seen by both registrations.
+    pub fn on_edge<F>(&self, pin: Pin, edge: Edge, callback: F) -> Result<RegistrationId>
+    where
+        F: FnMut(PinEvent) + Send + 'static,
+    {
+        self.register(pin, edge, Handler::Callback(Box::new(callback)))
+    }
+
+    /// Receive a `PinEvent` on a channel for each interrupt on `pin`
+    ///
+    /// The registration is removed automatically once the receiver has
+    /// been dropped and the next event for it arrives.
+    pub fn subscribe(
+        &self,
+        pin: Pin,
+        edge: Edge,
+    ) -> Result<(RegistrationId, mpsc::Receiver<PinEvent>)> {
+        let (tx, rx) = mpsc::channel();
+        let id = self.register(pin, edge, Handler::Channel(tx))?;
+        Ok((id, rx))
+    }
+
+    /// Remove a registration
+    ///
+    /// A callback which is already running when this is called may
+    /// still complete after it returns.
+    pub fn remove(&self, id: RegistrationId) -> Result<()> {
+        match self.shared.remove(id.0)? {
+            true => Ok(()),
+            false => Err(Error::InvalidArgument(format!(
+                "no registration with id {}",
+                id.0
+            ))),
+        }
+    }
+
+    fn register(&self, pin: Pin, edge: Edge, handler: Handler) -> Result<RegistrationId> {
+        if edge == Edge::NoInterrupt {
+            return Err(Error::InvalidArgument(
+                "cannot dispatch Edge::NoInterrupt".to_owned(),
+            ));
+        }
+        if !self.is_running() {
+            return Err(stopped_error());
+        }
+        pin.set_edge(edge)?;
+        let mut devfile = File::open(format!("/sys/class/gpio/gpio{}/value", pin.get_pin()))?;
+        // reading the value acknowledges any interrupt which is already pending
+        get_value_from_file(&mut devfile)?;
+
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut registrations = self.shared.registrations();
+        // checked again under the lock, which the thread holds as it stops
+        if self.shared.stopped.load(Ordering::SeqCst) {
+            return Err(stopped_error());
+        }
+        self.shared.epoll.add(
+            &devfile,
+            EpollEvent::new(EpollFlags::EPOLLPRI | EpollFlags::EPOLLET, id),
+        )?;
+        registrations.insert(
+            id,
+            Registration {
+                pin,
+                devfile,
+                handler: Arc::new(Mutex::new(handler)),
+            },
+        );
+        Ok(RegistrationId(id))
+    }
+}
+
+impl fmt::Debug for Dispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pins: Vec<Pin> = self
+            .shared
+            .registrations()
+            .values()
+            .map(|r| r.pin)
+            .collect();
+        pins.sort_by_key(Pin::get_pin);
+        f.debug_struct("Dispatcher")
+            .field("running", &self.is_running())
+            .field("pins", &pins)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Dispatcher {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        let _ = self.shared.wake.write(1);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn stopped_error() -> Error {
+    Error::Unexpected("dispatcher thread has stopped".to_owned())
+}
+
+/// Marks the dispatcher as stopped when its thread exits, however it exits
+struct StoppedGuard<'a>(&'a Shared);
+
+impl Drop for StoppedGuard<'_> {
+    fn drop(&mut self) {
+        let mut registrations = self.0.registrations();
+        self.0.stopped.store(true, Ordering::SeqCst);
+        registrations.clear();
+    }
+}
+
+/// Body of the dispatcher thread
+fn run(shared: &Shared) {
+    let mut events = [EpollEvent::empty(); 16];
+    loop {
+        let count = match shared.epoll.wait(&mut events, EpollTimeout::NONE) {
+            Ok(count) => count,
+            Err(Errno::EINTR) => continue,
+            Err(_) => return,
+        };
+        for event in &events[..count] {
+            if event.data() == WAKE {
+                let _ = shared.wake.read();
+                if shared.shutdown.load(Ordering::SeqCst) {
+                    return;
+                }
+                continue;
+            }
+            let id = event.data();
+            let (pin, handler, value) = {
+                let mut registrations = shared.registrations();
+                match registrations.get_mut(&id) {
+                    Some(registration) => (
+                        registration.pin,
+                        registration.handler.clone(),
+                        get_value_from_file(&mut registration.devfile),
+                    ),
+                    // removed since epoll_wait returned
+                    None => continue,
+                }
+            };
+            let level = match value {
+                Ok(value) => Level::from(value),
+                Err(_) => continue,
+            };
+            let event = PinEvent {
+                pin,
+                level,
+                timestamp: Instant::now(),
+            };
+            let disconnected = match handler.lock() {
+                Ok(mut handler) => match *handler {
+                    // the panic is caught before it unwinds through the
+                    // guard, so a faulty callback does not poison it
+                    Handler::Callback(ref mut callback) => {
+                        panic::catch_unwind(AssertUnwindSafe(|| callback(event))).is_err()
+                    }
+                    Handler::Channel(ref tx) => tx.send(event).is_err(),
+                },
+                Err(_) => true,
+            };
+            if disconnected {
+                let _ = shared.remove(id);
+            }
+        }
+    }
+}
+
+#[test]
+fn stopped_dispatcher_test() {
+    let dispatcher = Dispatcher::new().unwrap();
+    assert!(dispatcher.is_running());
+    assert_eq!(
+        "Dispatcher { running: true, pins: [], .. }",
+        format!("{:?}", dispatcher)
+    );
+    // simulate the thread exiting on an epoll error
+    drop(StoppedGuard(&dispatcher.shared));
+    assert!(!dispatcher.is_running());
+    assert!(matches!(
+        dispatcher.on_edge(Pin::new(1), Edge::BothEdges, |_| {}),
+        Err(Error::Unexpected(_))
+    ));
+}
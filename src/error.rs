@@ -1,6 +1,11 @@
 use std::convert;
 use std::fmt;
 use std::io;
+use std::time::Duration;
+
+use crate::ConfigStep;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::RealtimeError;
 
 #[derive(Debug)]
 pub enum Error {
@@ -12,12 +17,26 @@ pub enum Error {
     InvalidPath(String),
     /// Operation not supported on target os
     Unsupported(String),
+    /// An argument was outside of the supported range
+    InvalidArgument(String),
+    /// Nothing happened within the given time
+    Timeout(Duration),
+    /// Applying real-time tuning to a thread failed
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Realtime(RealtimeError),
+    /// Applying a `PinConfig` failed at the given step
+    ConfigFailed { step: ConfigStep, error: Box<Error> },
+    /// An attribute did not hold the value written to it
+    VerificationFailed { expected: String, actual: String },
 }
 
 impl ::std::error::Error for Error {
     fn cause(&self) -> Option<&dyn std::error::Error> {
         match *self {
             Error::Io(ref e) => Some(e),
+            Error::ConfigFailed { ref error, .. } => Some(error.as_ref()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Error::Realtime(ref e) => Some(e),
             _ => None,
         }
     }
@@ -30,6 +49,22 @@ impl fmt::Display for Error {
             Error::Unexpected(ref s) => write!(f, "Unexpected: {}", s),
             Error::InvalidPath(ref s) => write!(f, "Invalid Path: {}", s),
             Error::Unsupported(ref s) => write!(f, "Operation not supported on target os: {}", s),
+            Error::InvalidArgument(ref s) => write!(f, "Invalid Argument: {}", s),
+            Error::Timeout(ref d) => write!(f, "Timed out after {:?}", d),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Error::Realtime(ref e) => write!(f, "Real-time tuning failed: {}", e),
+            Error::ConfigFailed {
+                ref step,
+                ref error,
+            } => write!(f, "Configuration failed at {} step: {}", step, error),
+            Error::VerificationFailed {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "Verification failed: expected {}, read back {}",
+                expected, actual
+            ),
         }
     }
 }
@@ -53,6 +88,27 @@ impl convert::From<Error> for io::Error {
             Error::Unexpected(err) => io::Error::new(io::ErrorKind::Unsupported, err),
             Error::InvalidPath(err) => io::Error::new(io::ErrorKind::InvalidInput, err),
             Error::Unsupported(err) => io::Error::new(io::ErrorKind::InvalidData, err),
+            Error::InvalidArgument(err) => io::Error::new(io::ErrorKind::InvalidInput, err),
+            err @ Error::Timeout(_) => io::Error::new(io::ErrorKind::TimedOut, err.to_string()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Error::Realtime(err) => match err {
+                RealtimeError::Scheduler(errno)
+                | RealtimeError::Affinity(errno)
+                | RealtimeError::MemoryLock(errno) => io::Error::new(
+                    io::Error::from(errno).kind(),
+                    format!("Real-time tuning failed: {}", err),
+                ),
+                RealtimeError::InvalidPriority { .. } => {
+                    io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+                }
+            },
+            Error::ConfigFailed { step, error } => {
+                let err = io::Error::from(*error);
+                io::Error::new(err.kind(), format!("{} step: {}", step, err))
+            }
+            err @ Error::VerificationFailed { .. } => {
+                io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+            }
         }
     }
 }
@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use crate::{Level, PinEvent, PinPoller, Result};
+
+/// Blocking iterator over the interrupts of a pin
+///
+/// Each call to `next()` blocks until an interrupt occurs and yields
+/// a `PinEvent` with the value of the pin read when the interrupt was
+/// handled.  Interrupts which occur between calls to `next()` are not
+/// lost.  Iteration ends after an error has been yielded, or when no
+/// interrupt occurs within the idle timeout if one has been set.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Edge, Level, Pin};
+///
+/// let button = Pin::new(23);
+/// button.set_edge(Edge::BothEdges).unwrap();
+/// let events = button
+///     .events()
+///     .unwrap()
+///     .idle_timeout(Duration::from_secs(10))
+///     .until_level(Level::Low);
+/// for event in events {
+///     println!("{:?}", event.unwrap().level);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PinEvents {
+    poller: PinPoller,
+    idle_timeout: Option<Duration>,
+    done: bool,
+}
+
+impl PinEvents {
+    pub(crate) fn new(mut poller: PinPoller) -> Result<PinEvents> {
+        // acknowledge any interrupt which happened before iteration started
+        poller.read_value()?;
+        Ok(PinEvents {
+            poller,
+            idle_timeout: None,
+            done: false,
+        })
+    }
+
+    /// End the iteration when no interrupt occurs for `timeout`
+    pub fn idle_timeout(mut self, timeout: Duration) -> PinEvents {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Yield events up to and including the first one at `level`
+    pub fn until_level(self, level: Level) -> UntilLevel {
+        UntilLevel {
+            events: self,
+            level,
+            reached: false,
+        }
+    }
+
+    /// Get the poller the events are read from
+    pub fn into_poller(self) -> PinPoller {
+        self.poller
+    }
+
+    fn next_event(&mut self) -> Result<Option<PinEvent>> {
+        let deadline = self.idle_timeout.map(|t| Instant::now() + t);
+        if !self.poller.wait_for_event(deadline)? {
+            return Ok(None);
+        }
+        let level = Level::from(self.poller.read_value()?);
+        Ok(Some(PinEvent {
+            pin: self.poller.get_pin(),
+            level,
+            timestamp: Instant::now(),
+        }))
+    }
+}
+
+impl Iterator for PinEvents {
+    type Item = Result<PinEvent>;
+
+    fn next(&mut self) -> Option<Result<PinEvent>> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_event().transpose();
+        self.done = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
+
+/// Iterator returned by `PinEvents::until_level`
+#[derive(Debug)]
+pub struct UntilLevel {
+    events: PinEvents,
+    level: Level,
+    reached: bool,
+}
+
+impl Iterator for UntilLevel {
+    type Item = Result<PinEvent>;
+
+    fn next(&mut self) -> Option<Result<PinEvent>> {
+        if self.reached {
+            return None;
+        }
+        let next = self.events.next();
+        if let Some(Ok(ref event)) = next {
+            self.reached = event.level == self.level;
+        }
+        next
+    }
+}
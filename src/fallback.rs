@@ -0,0 +1,343 @@
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "async-tokio")]
+use futures::Stream;
+#[cfg(feature = "async-tokio")]
+use std::future::Future;
+#[cfg(feature = "async-tokio")]
+use std::task::{Context, Poll};
+
+#[cfg(feature = "async-tokio")]
+use crate::PinValueStream;
+use crate::{Edge, Error, Pin, PinHandle, PinPoller, Result};
+
+/// How often the value of a pin without interrupt support is sampled
+///
+/// Intervals must be non-zero, and `min` must not be greater than
+/// `max`; other rates are rejected with `Error::InvalidArgument`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleRate {
+    /// Sample at a fixed interval
+    Fixed(Duration),
+    /// Sample every `min` after a change, backing off to `max` while the pin is idle
+    Adaptive { min: Duration, max: Duration },
+}
+
+/// Set the edge of `pin`, returning false if the pin has no interrupt
+///
+/// Kernels hide the "edge" attribute of pins without an IRQ.  Writing
+/// to the missing attribute fails with EACCES rather than ENOENT, as
+/// sysfs refuses to create the file, so check that it exists first.
+/// Some drivers instead expose the attribute and reject the write.
+fn set_edge_if_supported(pin: &Pin, edge: Edge) -> Result<bool> {
+    if fs::metadata(format!("/sys/class/gpio/gpio{}/edge", pin.get_pin())).is_err() {
+        return Ok(false);
+    }
+    match pin.set_edge(edge) {
+        Ok(()) => Ok(true),
+        Err(ref err) if lacks_interrupt_support(err) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Determines whether a failure to set the edge means the pin has no interrupt
+fn lacks_interrupt_support(err: &Error) -> bool {
+    match *err {
+        Error::Io(ref e) => matches!(
+            e.raw_os_error(),
+            Some(nix::libc::ENOENT)
+                | Some(nix::libc::EINVAL)
+                | Some(nix::libc::EIO)
+                | Some(nix::libc::ENXIO)
+        ),
+        _ => false,
+    }
+}
+
+/// Change detection for a sampled pin
+#[derive(Debug)]
+struct Sampler {
+    edge: Edge,
+    rate: SampleRate,
+    interval: Duration,
+    last: u8,
+}
+
+impl Sampler {
+    fn new(edge: Edge, rate: SampleRate, initial: u8) -> Result<Sampler> {
+        let interval = match rate {
+            SampleRate::Fixed(interval) => interval,
+            SampleRate::Adaptive { min, max } if min > max => {
+                return Err(Error::InvalidArgument(format!(
+                    "sample interval min {:?} is greater than max {:?}",
+                    min, max
+                )))
+            }
+            SampleRate::Adaptive { min, .. } => min,
+        };
+        if interval.is_zero() {
+            return Err(Error::InvalidArgument(
+                "sample interval must not be zero".to_owned(),
+            ));
+        }
+        Ok(Sampler {
+            edge,
+            rate,
+            interval,
+            last: initial,
+        })
+    }
+
+    /// Record a sample, returning it if it is a change matching the edge
+    fn update(&mut self, value: u8) -> Option<u8> {
+        let changed = value != self.last;
+        self.last = value;
+        if let SampleRate::Adaptive { min, max } = self.rate {
+            self.interval = match changed {
+                true => min,
+                false => self.interval.saturating_mul(2).min(max),
+            };
+        }
+        let wanted = match self.edge {
+            Edge::NoInterrupt => false,
+            Edge::RisingEdge => value == 1,
+            Edge::FallingEdge => value == 0,
+            Edge::BothEdges => true,
+        };
+        match changed && wanted {
+            true => Some(value),
+            false => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Source {
+    Interrupt(PinPoller),
+    Sampled(PinHandle, Sampler),
+}
+
+/// A poller which samples the pin when it has no interrupt support
+///
+/// Many GPIO expanders provide pins without an "edge" attribute, or
+/// reject writes to it, so `PinPoller` cannot be used with them.
+/// When created, a `FallbackPoller` tries to set the requested edge;
+/// if the pin has no "edge" attribute or the write is rejected, it
+/// instead reads the value at the provided `SampleRate` and reports
+/// changes matching the edge.  Either way `poll` behaves like
+/// `PinPoller::poll`.
+///
+/// Note that sampling can miss pulses shorter than the sample
+/// interval.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Edge, Pin, SampleRate};
+///
+/// let input = Pin::new(500);
+/// let rate = SampleRate::Adaptive {
+///     min: Duration::from_millis(1),
+///     max: Duration::from_millis(50),
+/// };
+/// let mut poller = input.get_fallback_poller(Edge::BothEdges, rate).unwrap();
+/// loop {
+///     if let Some(value) = poller.poll(1000).unwrap() {
+///         println!("{}", value);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FallbackPoller {
+    pin: Pin,
+    source: Source,
+}
+
+impl FallbackPoller {
+    /// Create a poller for `pin` reporting changes matching `edge`
+    pub fn new(pin: Pin, edge: Edge, rate: SampleRate) -> Result<FallbackPoller> {
+        let source = match set_edge_if_supported(&pin, edge)? {
+            true => Source::Interrupt(pin.get_poller()?),
+            false => {
+                let handle = pin.get_handle()?;
+                let initial = handle.get_value()?;
+                Source::Sampled(handle, Sampler::new(edge, rate, initial)?)
+            }
+        };
+        Ok(FallbackPoller { pin, source })
+    }
+
+    /// Get the pin associated with this FallbackPoller
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Determines whether the pin's interrupt is used rather than sampling
+    pub fn uses_interrupts(&self) -> bool {
+        matches!(self.source, Source::Interrupt(_))
+    }
+
+    /// Block until the pin changes
+    ///
+    /// This has the same semantics as `PinPoller::poll`: it returns
+    /// `Some(value)` when a change matching the edge is detected, or
+    /// `None` if no change occurs within `timeout_ms` milliseconds.  A
+    /// negative timeout waits forever.
+    pub fn poll(&mut self, timeout_ms: isize) -> Result<Option<u8>> {
+        let (handle, sampler) = match self.source {
+            Source::Interrupt(ref mut poller) => return poller.poll(timeout_ms),
+            Source::Sampled(ref handle, ref mut sampler) => (handle, sampler),
+        };
+        let deadline = u64::try_from(timeout_ms)
+            .ok()
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+        loop {
+            let wait = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+                    sampler.interval.min(remaining)
+                }
+                None => sampler.interval,
+            };
+            thread::sleep(wait);
+            if let Some(value) = sampler.update(handle.get_value()?) {
+                return Ok(Some(value));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+enum StreamSource {
+    Interrupt(PinValueStream),
+    Sampled(PinHandle, Sampler, std::pin::Pin<Box<tokio::time::Sleep>>),
+}
+
+/// A stream of pin values which samples the pin when it has no interrupt support
+///
+/// This is the tokio counterpart of `FallbackPoller`: it yields the
+/// same items as a `PinValueStream`, using the pin's interrupt where
+/// possible and sampling its value otherwise.
+///
+/// This type is only available when the `async-tokio` crate feature
+/// is enabled.
+#[cfg(feature = "async-tokio")]
+pub struct FallbackValueStream {
+    pin: Pin,
+    source: StreamSource,
+}
+
+#[cfg(feature = "async-tokio")]
+impl FallbackValueStream {
+    /// Create a stream for `pin` yielding changes matching `edge`
+    pub fn new(pin: Pin, edge: Edge, rate: SampleRate) -> Result<FallbackValueStream> {
+        let source = match set_edge_if_supported(&pin, edge)? {
+            true => StreamSource::Interrupt(pin.get_value_stream()?),
+            false => {
+                let handle = pin.get_handle()?;
+                let initial = handle.get_value()?;
+                let sampler = Sampler::new(edge, rate, initial)?;
+                let sleep = Box::pin(tokio::time::sleep(sampler.interval));
+                StreamSource::Sampled(handle, sampler, sleep)
+            }
+        };
+        Ok(FallbackValueStream { pin, source })
+    }
+
+    /// Get the pin associated with this stream
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Determines whether the pin's interrupt is used rather than sampling
+    pub fn uses_interrupts(&self) -> bool {
+        matches!(self.source, StreamSource::Interrupt(_))
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+impl Stream for FallbackValueStream {
+    type Item = Result<u8>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        match self.source {
+            StreamSource::Interrupt(ref mut stream) => std::pin::Pin::new(stream).poll_next(cx),
+            StreamSource::Sampled(ref handle, ref mut sampler, ref mut sleep) => loop {
+                futures::ready!(sleep.as_mut().poll(cx));
+                let value = handle.get_value();
+                let next = tokio::time::Instant::now() + sampler.interval;
+                sleep.as_mut().reset(next);
+                match value {
+                    Ok(value) => {
+                        if let Some(value) = sampler.update(value) {
+                            return Poll::Ready(Some(Ok(value)));
+                        }
+                    }
+                    Err(err) => return Poll::Ready(Some(Err(err))),
+                }
+            },
+        }
+    }
+}
+
+#[test]
+fn sampler_test() {
+    let ms = Duration::from_millis;
+    let rate = SampleRate::Adaptive {
+        min: ms(1),
+        max: ms(8),
+    };
+    let mut sampler = Sampler::new(Edge::RisingEdge, rate, 0).unwrap();
+    assert_eq!(ms(1), sampler.interval);
+
+    // the interval backs off while the pin is idle
+    assert_eq!(None, sampler.update(0));
+    assert_eq!(ms(2), sampler.interval);
+    for _ in 0..5 {
+        assert_eq!(None, sampler.update(0));
+    }
+    assert_eq!(ms(8), sampler.interval);
+
+    // a change resets the interval and is reported if it matches the edge
+    assert_eq!(Some(1), sampler.update(1));
+    assert_eq!(ms(1), sampler.interval);
+    assert_eq!(None, sampler.update(1));
+    assert_eq!(None, sampler.update(0));
+    assert_eq!(ms(1), sampler.interval);
+
+    // zero or inverted intervals would never wait between samples
+    for rate in [
+        SampleRate::Fixed(Duration::ZERO),
+        SampleRate::Adaptive {
+            min: Duration::ZERO,
+            max: ms(8),
+        },
+        SampleRate::Adaptive {
+            min: ms(8),
+            max: ms(1),
+        },
+    ] {
+        assert!(matches!(
+            Sampler::new(Edge::BothEdges, rate, 0),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
+
+#[test]
+fn lacks_interrupt_support_test() {
+    let os_error = |errno| Error::Io(std::io::Error::from_raw_os_error(errno));
+    assert!(lacks_interrupt_support(&os_error(nix::libc::ENOENT)));
+    assert!(lacks_interrupt_support(&os_error(nix::libc::EIO)));
+    assert!(!lacks_interrupt_support(&os_error(nix::libc::EACCES)));
+    assert!(!lacks_interrupt_support(&Error::Unexpected("".into())));
+}
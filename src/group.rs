@@ -0,0 +1,165 @@
+use crate::{Error, Pin, PinHandle, Result};
+
+/// A group of pins read and written together as the bits of a word
+///
+/// Bit `n` of a word corresponds to the `n`th pin the group was
+/// created with, so a group holds at most 32 pins.  The value files of
+/// all pins are kept open to keep the skew between bits small, and
+/// only the pins whose value changes are written.
+///
+/// A group may have a strobe pin, which is pulsed high and low again
+/// after each write, and a latch pin, which is pulsed before each
+/// read.  Use `Pin::set_active_low` on these pins if they are active
+/// low.
+///
+/// # Example
+///
+/// ```no_run
+/// use sysfs_gpio::{Pin, PinGroup};
+///
+/// let pins: Vec<Pin> = (20..28).map(Pin::new).collect();
+/// let mut bus = PinGroup::new(&pins)
+///     .unwrap()
+///     .with_strobe(Pin::new(28))
+///     .unwrap();
+/// bus.write_word(0xa5).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PinGroup {
+    handles: Vec<PinHandle>,
+    strobe: Option<PinHandle>,
+    latch: Option<PinHandle>,
+    /// Last value written to each pin
+    state: u32,
+    /// Mask of the pins whose value in `state` is known
+    known: u32,
+}
+
+/// Compute the mask of pins which must be written to output `word`
+fn bits_to_write(state: u32, known: u32, word: u32, mask: u32) -> u32 {
+    mask & ((state ^ word) | !known)
+}
+
+impl PinGroup {
+    /// Create a group from a list of exported pins
+    ///
+    /// The pins should already be configured as outputs or inputs as
+    /// appropriate.  This will return an error if more than 32 pins
+    /// are provided.
+    pub fn new(pins: &[Pin]) -> Result<PinGroup> {
+        if pins.len() > 32 {
+            return Err(Error::InvalidArgument(format!(
+                "PinGroup supports at most 32 pins, got {}",
+                pins.len()
+            )));
+        }
+        let handles = pins
+            .iter()
+            .map(|pin| pin.get_handle())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PinGroup {
+            handles,
+            strobe: None,
+            latch: None,
+            state: 0,
+            known: 0,
+        })
+    }
+
+    /// Use `pin` as a strobe which is pulsed after each write
+    pub fn with_strobe(mut self, pin: Pin) -> Result<PinGroup> {
+        self.strobe = Some(pin.get_handle()?);
+        Ok(self)
+    }
+
+    /// Use `pin` as a latch which is pulsed before each read
+    pub fn with_latch(mut self, pin: Pin) -> Result<PinGroup> {
+        self.latch = Some(pin.get_handle()?);
+        Ok(self)
+    }
+
+    /// Get the pins of the group, least significant bit first
+    pub fn pins(&self) -> Vec<Pin> {
+        self.handles.iter().map(|h| h.get_pin()).collect()
+    }
+
+    /// Get the number of pins in the group
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Determines whether the group has no pins
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Get the mask covering every pin of the group
+    fn full_mask(&self) -> u32 {
+        match self.handles.len() {
+            32 => u32::MAX,
+            n => (1 << n) - 1,
+        }
+    }
+
+    /// Write `word` to all pins of the group
+    ///
+    /// Bits above the size of the group are ignored.
+    pub fn write_word(&mut self, word: u32) -> Result<()> {
+        self.write_masked(word, u32::MAX)
+    }
+
+    /// Write the bits of `word` selected by `mask`
+    ///
+    /// Pins whose bit is clear in `mask` are left untouched.  Pins
+    /// which already hold the requested value are not written again,
+    /// which relies on nothing else changing the pins behind the
+    /// group's back; call `invalidate` if that may have happened.
+    pub fn write_masked(&mut self, word: u32, mask: u32) -> Result<()> {
+        let mask = mask & self.full_mask();
+        let dirty = bits_to_write(self.state, self.known, word, mask);
+        for (bit, handle) in self.handles.iter().enumerate() {
+            if dirty & (1 << bit) != 0 {
+                // forget the pin until the write has succeeded
+                self.known &= !(1 << bit);
+                handle.set_value(((word >> bit) & 1) as u8)?;
+                self.state = (self.state & !(1 << bit)) | (word & (1 << bit));
+                self.known |= 1 << bit;
+            }
+        }
+        if let Some(ref strobe) = self.strobe {
+            strobe.set_value(1)?;
+            strobe.set_value(0)?;
+        }
+        Ok(())
+    }
+
+    /// Read the value of all pins of the group as a word
+    pub fn read_word(&self) -> Result<u32> {
+        if let Some(ref latch) = self.latch {
+            latch.set_value(1)?;
+            latch.set_value(0)?;
+        }
+        let mut word = 0;
+        for (bit, handle) in self.handles.iter().enumerate() {
+            word |= u32::from(handle.get_value()?) << bit;
+        }
+        Ok(word)
+    }
+
+    /// Forget the values last written so the next write updates every pin
+    pub fn invalidate(&mut self) {
+        self.known = 0;
+    }
+}
+
+#[test]
+fn bits_to_write_test() {
+    // nothing known yet: every masked pin is written
+    assert_eq!(0xff, bits_to_write(0, 0, 0x0f, 0xff));
+    // only changed pins are written
+    assert_eq!(0x33, bits_to_write(0x0f, 0xff, 0x3c, 0xff));
+    // pins outside the mask are left alone
+    assert_eq!(0x13, bits_to_write(0x0f, 0xff, 0x3c, 0x1f));
+    // unknown pins are written even if the cached value matches
+    assert_eq!(0x81, bits_to_write(0x01, 0x7e, 0x01, 0xff));
+}
@@ -0,0 +1,63 @@
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::FileExt;
+
+use crate::{Error, Pin, Result};
+
+/// An open handle on the value file of a Pin
+///
+/// `Pin::get_value` and `Pin::set_value` open the value file on
+/// every call.  A `PinHandle` keeps the file open so that reading and
+/// writing the value costs a single system call, which matters when a
+/// pin is accessed in a tight loop or several pins have to change
+/// with as little skew as possible.
+#[derive(Debug)]
+pub struct PinHandle {
+    pin_num: u64,
+    devfile: File,
+}
+
+impl PinHandle {
+    /// Open the value file of an exported pin
+    pub fn new(pin_num: u64) -> Result<PinHandle> {
+        let devfile = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(format!("/sys/class/gpio/gpio{}/value", pin_num))?;
+        Ok(PinHandle { pin_num, devfile })
+    }
+
+    /// Get the pin associated with this PinHandle
+    ///
+    /// Note that this will be a new Pin object with the
+    /// proper pin number.
+    pub fn get_pin(&self) -> Pin {
+        Pin::new(self.pin_num)
+    }
+
+    /// Get the value of the Pin (0 or 1)
+    pub fn get_value(&self) -> Result<u8> {
+        let mut buf = [0u8; 2];
+        let n = self.devfile.read_at(&mut buf, 0)?;
+        match &buf[..n] {
+            [b'0', ..] => Ok(0),
+            [b'1', ..] => Ok(1),
+            other => Err(Error::Unexpected(format!(
+                "value file contents {:?}",
+                String::from_utf8_lossy(other)
+            ))),
+        }
+    }
+
+    /// Set the value of the Pin
+    ///
+    /// A 0 value will set the pin low and any other value will
+    /// set the pin high.
+    pub fn set_value(&self, value: u8) -> Result<()> {
+        let buf: &[u8] = match value {
+            0 => b"0",
+            _ => b"1",
+        };
+        self.devfile.write_all_at(buf, 0)?;
+        Ok(())
+    }
+}
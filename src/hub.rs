@@ -0,0 +1,186 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::{Error, Level, Pin, PinEvent, Result};
+
+/// Shares the interrupts of one pin between any number of async tasks
+///
+/// Each `PinStream` owns its own file descriptor, so tasks which all
+/// open a stream on the same pin each read every interrupt and compete
+/// for the readiness notifications.  A `PinEventHub` instead reads
+/// each interrupt once on a task of its own and broadcasts the
+/// resulting `PinEvent` to every subscriber.
+///
+/// Events are buffered per hub up to the capacity given on creation.
+/// A subscriber which falls further behind than that loses the oldest
+/// events, and its next `recv()` returns
+/// `broadcast::error::RecvError::Lagged` with the number of events
+/// which were skipped.  Once the pin can no longer be read, the hub
+/// stops and subscribers see `RecvError::Closed`; the error which
+/// stopped it is available from `take_error`.
+///
+/// This type is only available when the `async-tokio` crate feature
+/// is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use sysfs_gpio::{Edge, Pin, PinEventHub};
+///
+/// # async fn run() -> sysfs_gpio::Result<()> {
+/// let button = Pin::new(23);
+/// button.set_edge(Edge::BothEdges)?;
+/// let hub = PinEventHub::new(button, 16)?;
+/// let mut events = hub.subscribe();
+/// tokio::spawn(async move {
+///     while let Ok(event) = events.recv().await {
+///         println!("{:?}", event.level);
+///     }
+/// });
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PinEventHub {
+    pin: Pin,
+    // only the task holds a strong sender, so that subscribers see
+    // `Closed` as soon as it stops
+    tx: broadcast::WeakSender<PinEvent>,
+    error: Arc<Mutex<Option<Error>>>,
+    task: JoinHandle<()>,
+}
+
+impl PinEventHub {
+    /// Start broadcasting the interrupts of `pin`, buffering up to `capacity` events
+    ///
+    /// This must be called from within a tokio runtime.  You should
+    /// probably call `set_edge()` on the pin before using this.
+    pub fn new(pin: Pin, capacity: usize) -> Result<PinEventHub> {
+        PinEventHub::with_stream(pin, pin.get_value_stream()?, capacity)
+    }
+
+    /// Start broadcasting the values of `stream` as events of `pin`
+    ///
+    /// `new` is this with the pin's own `PinValueStream`; other
+    /// streams of pin values, such as a `DebouncedStream`, can be
+    /// shared with this.  This must be called from within a tokio
+    /// runtime.
+    pub fn with_stream<S>(pin: Pin, mut stream: S, capacity: usize) -> Result<PinEventHub>
+    where
+        S: Stream<Item = Result<u8>> + Unpin + Send + 'static,
+    {
+        if capacity == 0 {
+            return Err(Error::InvalidArgument(
+                "PinEventHub capacity must be at least 1".to_owned(),
+            ));
+        }
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|err| Error::Io(io::Error::other(err)))?;
+        let (tx, _) = broadcast::channel(capacity);
+        let weak = tx.downgrade();
+        let error = Arc::new(Mutex::new(None));
+        let task_error = error.clone();
+        let task = runtime.spawn(async move {
+            while let Some(value) = stream.next().await {
+                let value = match value {
+                    Ok(value) => value,
+                    Err(err) => {
+                        *task_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(err);
+                        return;
+                    }
+                };
+                // an error only means there are no subscribers right now
+                let _ = tx.send(PinEvent {
+                    pin,
+                    level: Level::from(value),
+                    timestamp: Instant::now(),
+                });
+            }
+        });
+        Ok(PinEventHub {
+            pin,
+            tx: weak,
+            error,
+            task,
+        })
+    }
+
+    /// Get the pin whose interrupts are broadcast
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Subscribe to the events which occur from now on
+    ///
+    /// If the hub has stopped, the receiver is already closed.
+    pub fn subscribe(&self) -> broadcast::Receiver<PinEvent> {
+        match self.tx.upgrade() {
+            Some(tx) => tx.subscribe(),
+            None => broadcast::channel(1).1,
+        }
+    }
+
+    /// Determines whether the hub is still reading interrupts
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Take the error which stopped the hub, if any
+    ///
+    /// Returns `None` while the hub is running, if the stream of pin
+    /// values ended without an error, or if the error has already
+    /// been taken.
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+impl Drop for PinEventHub {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[tokio::test]
+async fn pin_event_hub_test() {
+    use futures::channel::mpsc::unbounded;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let (tx, rx) = unbounded::<Result<u8>>();
+    let hub = PinEventHub::with_stream(Pin::new(1), rx, 2).unwrap();
+    let mut first = hub.subscribe();
+    let mut second = hub.subscribe();
+    let mut lagging = hub.subscribe();
+
+    // every subscriber sees every event
+    for (value, level) in [(1, Level::High), (0, Level::Low), (1, Level::High)] {
+        tx.unbounded_send(Ok(value)).unwrap();
+        for events in [&mut first, &mut second] {
+            let event = events.recv().await.unwrap();
+            assert_eq!((Pin::new(1), level), (event.pin, event.level));
+        }
+    }
+
+    // a subscriber which falls behind loses the oldest events
+    assert!(matches!(lagging.recv().await, Err(RecvError::Lagged(1))));
+    assert_eq!(Level::Low, lagging.recv().await.unwrap().level);
+    assert_eq!(Level::High, lagging.recv().await.unwrap().level);
+
+    // an error stops the hub and is kept for take_error
+    assert!(hub.take_error().is_none());
+    tx.unbounded_send(Err(Error::Unexpected("gone".to_owned())))
+        .unwrap();
+    assert!(matches!(first.recv().await, Err(RecvError::Closed)));
+    assert!(matches!(second.recv().await, Err(RecvError::Closed)));
+    assert!(matches!(hub.take_error(), Some(Error::Unexpected(_))));
+    assert!(hub.take_error().is_none());
+    assert!(matches!(
+        hub.subscribe().recv().await,
+        Err(RecvError::Closed)
+    ));
+}
@@ -0,0 +1,277 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::sysfs::{parse_attribute, read_attribute, write_attribute};
+use crate::{Error, Result};
+
+/// A trigger which controls an LED from within the kernel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LedTrigger {
+    /// No trigger; the LED is controlled through its brightness
+    None,
+    /// Blink with the periods set by `Led::set_delay_on` and `Led::set_delay_off`
+    Timer,
+    /// Double flash at a rate following the system load
+    Heartbeat,
+    /// Follow the link state and traffic of a network device
+    Netdev,
+    /// Any other trigger, by its sysfs name
+    Other(String),
+}
+
+impl LedTrigger {
+    fn from_name(name: &str) -> LedTrigger {
+        match name {
+            "none" => LedTrigger::None,
+            "timer" => LedTrigger::Timer,
+            "heartbeat" => LedTrigger::Heartbeat,
+            "netdev" => LedTrigger::Netdev,
+            other => LedTrigger::Other(other.to_owned()),
+        }
+    }
+
+    /// Get the name of the trigger as used by sysfs
+    pub fn name(&self) -> &str {
+        match self {
+            LedTrigger::None => "none",
+            LedTrigger::Timer => "timer",
+            LedTrigger::Heartbeat => "heartbeat",
+            LedTrigger::Netdev => "netdev",
+            LedTrigger::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for LedTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An event shown by an LED with the `netdev` trigger
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetdevEvent {
+    /// Light while the link is up
+    Link,
+    /// Flash when packets are transmitted
+    Tx,
+    /// Flash when packets are received
+    Rx,
+}
+
+/// An LED exposed through `/sys/class/leds`
+///
+/// LEDs bound to a kernel driver such as `gpio-leds` cannot be
+/// exported as a `Pin`; they are instead controlled through the
+/// attribute files of `/sys/class/leds/<name>`.  An LED either
+/// follows its `brightness` directly or is driven by a kernel
+/// trigger such as `timer` or `heartbeat`.  Note that setting the
+/// brightness to 0 also removes any trigger.
+///
+/// As with `PwmChannel`, the root defaults to `/sys/class/leds` and
+/// can be changed with `with_root`; it only applies to the `Led` it
+/// is set on.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Led, LedTrigger};
+///
+/// let led = Led::new("status:green");
+/// println!("available triggers: {:?}", led.get_triggers().unwrap());
+/// led.set_trigger(&LedTrigger::Timer).unwrap();
+/// led.set_delay_on(Duration::from_millis(100)).unwrap();
+/// led.set_delay_off(Duration::from_millis(900)).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Led {
+    root: PathBuf,
+    name: String,
+}
+
+impl Led {
+    /// Create a new Led for the LED named `name`
+    pub fn new<S: Into<String>>(name: S) -> Led {
+        Led {
+            root: PathBuf::from("/sys/class/leds"),
+            name: name.into(),
+        }
+    }
+
+    /// Look the LED up under `root` instead of `/sys/class/leds`
+    pub fn with_root<P: Into<PathBuf>>(mut self, root: P) -> Led {
+        self.root = root.into();
+        self
+    }
+
+    /// Get the name of the LED
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the sysfs directory of the LED
+    pub fn get_path(&self) -> PathBuf {
+        self.root.join(&self.name)
+    }
+
+    /// Determines whether the LED exists
+    pub fn exists(&self) -> bool {
+        fs::metadata(self.get_path()).is_ok()
+    }
+
+    fn write_to_device_file(&self, dev_file_name: &str, value: &str) -> Result<()> {
+        write_attribute(&self.get_path().join(dev_file_name), value)
+    }
+
+    fn read_from_device_file(&self, dev_file_name: &str) -> Result<String> {
+        read_attribute(&self.get_path().join(dev_file_name))
+    }
+
+    fn read_number<T: std::str::FromStr>(&self, dev_file_name: &str) -> Result<T> {
+        parse_attribute(&self.get_path().join(dev_file_name))
+    }
+
+    /// Get the brightness of the LED
+    pub fn get_brightness(&self) -> Result<u32> {
+        self.read_number("brightness")
+    }
+
+    /// Set the brightness of the LED, from 0 to `get_max_brightness()`
+    ///
+    /// LEDs driven by a GPIO are either on or off, so any non-zero
+    /// brightness lights them fully.
+    pub fn set_brightness(&self, brightness: u32) -> Result<()> {
+        self.write_to_device_file("brightness", &brightness.to_string())
+    }
+
+    /// Get the maximum brightness supported by the LED
+    pub fn get_max_brightness(&self) -> Result<u32> {
+        self.read_number("max_brightness")
+    }
+
+    fn read_triggers(&self) -> Result<(Vec<LedTrigger>, Option<LedTrigger>)> {
+        let s = self.read_from_device_file("trigger")?;
+        let mut triggers = Vec::new();
+        let mut current = None;
+        for word in s.split_whitespace() {
+            match word.strip_prefix('[').and_then(|w| w.strip_suffix(']')) {
+                Some(name) => {
+                    current = Some(LedTrigger::from_name(name));
+                    triggers.push(LedTrigger::from_name(name));
+                }
+                None => triggers.push(LedTrigger::from_name(word)),
+            }
+        }
+        Ok((triggers, current))
+    }
+
+    /// Get the triggers the LED can be attached to
+    pub fn get_triggers(&self) -> Result<Vec<LedTrigger>> {
+        Ok(self.read_triggers()?.0)
+    }
+
+    /// Get the trigger the LED is currently attached to
+    pub fn get_trigger(&self) -> Result<LedTrigger> {
+        match self.read_triggers()?.1 {
+            Some(trigger) => Ok(trigger),
+            None => Err(Error::Unexpected(
+                "trigger file has no current trigger".to_owned(),
+            )),
+        }
+    }
+
+    /// Attach the LED to a trigger
+    ///
+    /// Attaching a trigger creates its attribute files, so settings
+    /// such as `set_delay_on` must be made after the trigger is set.
+    pub fn set_trigger(&self, trigger: &LedTrigger) -> Result<()> {
+        self.write_to_device_file("trigger", trigger.name())
+    }
+
+    /// Get how long the LED is lit in each blink of the `timer` trigger
+    pub fn get_delay_on(&self) -> Result<Duration> {
+        Ok(Duration::from_millis(self.read_number("delay_on")?))
+    }
+
+    /// Set how long the LED is lit in each blink of the `timer` trigger
+    ///
+    /// The delay is rounded down to whole milliseconds.
+    pub fn set_delay_on(&self, delay: Duration) -> Result<()> {
+        self.write_to_device_file("delay_on", &delay.as_millis().to_string())
+    }
+
+    /// Get how long the LED is dark in each blink of the `timer` trigger
+    pub fn get_delay_off(&self) -> Result<Duration> {
+        Ok(Duration::from_millis(self.read_number("delay_off")?))
+    }
+
+    /// Set how long the LED is dark in each blink of the `timer` trigger
+    ///
+    /// The delay is rounded down to whole milliseconds.
+    pub fn set_delay_off(&self, delay: Duration) -> Result<()> {
+        self.write_to_device_file("delay_off", &delay.as_millis().to_string())
+    }
+
+    /// Set the network device followed by the `netdev` trigger
+    pub fn set_netdev_device(&self, device: &str) -> Result<()> {
+        self.write_to_device_file("device_name", device)
+    }
+
+    /// Choose whether the `netdev` trigger shows an event
+    pub fn set_netdev_event(&self, event: NetdevEvent, enabled: bool) -> Result<()> {
+        self.write_to_device_file(
+            match event {
+                NetdevEvent::Link => "link",
+                NetdevEvent::Tx => "tx",
+                NetdevEvent::Rx => "rx",
+            },
+            if enabled { "1" } else { "0" },
+        )
+    }
+}
+
+#[test]
+fn led_test() {
+    let sysfs = crate::sysfs::FakeSysfs::new("leds");
+    let led = Led::new("status:green").with_root(&sysfs.root);
+    assert!(!led.exists());
+    sysfs.file("status:green/max_brightness", "255\n");
+    sysfs.file(
+        "status:green/trigger",
+        "none kbd-capslock [heartbeat] timer netdev\n",
+    );
+
+    assert_eq!(255, led.get_max_brightness().unwrap());
+    led.set_brightness(128).unwrap();
+    assert_eq!(128, led.get_brightness().unwrap());
+    sysfs.file("status:green/brightness", "4294967296\n");
+    assert!(matches!(led.get_brightness(), Err(Error::Unexpected(_))));
+
+    assert_eq!(
+        vec![
+            LedTrigger::None,
+            LedTrigger::Other("kbd-capslock".to_owned()),
+            LedTrigger::Heartbeat,
+            LedTrigger::Timer,
+            LedTrigger::Netdev,
+        ],
+        led.get_triggers().unwrap()
+    );
+    assert_eq!(LedTrigger::Heartbeat, led.get_trigger().unwrap());
+    led.set_trigger(&LedTrigger::Timer).unwrap();
+    assert_eq!("timer", sysfs.read("status:green/trigger"));
+    // sysfs lists the triggers rather than echoing back what was written
+    assert!(led.get_trigger().is_err());
+
+    led.set_delay_on(Duration::from_millis(100)).unwrap();
+    led.set_delay_off(Duration::from_secs(1)).unwrap();
+    assert_eq!(Duration::from_millis(100), led.get_delay_on().unwrap());
+    assert_eq!(Duration::from_secs(1), led.get_delay_off().unwrap());
+
+    led.set_netdev_device("eth0").unwrap();
+    led.set_netdev_event(NetdevEvent::Rx, true).unwrap();
+    assert_eq!("1", sysfs.read("status:green/rx"));
+}
@@ -51,12 +51,14 @@ extern crate tokio;
 
 use std::io;
 use std::io::prelude::*;
-#[cfg(any(target_os = "linux", target_os = "android", feature = "async-tokio"))]
+#[cfg(any(target_os = "linux", target_os = "android", feature = "mio-evented"))]
 use std::io::SeekFrom;
 #[cfg(any(feature = "async-tokio", feature = "mio-evented"))]
 use std::os::fd::{AsRawFd, RawFd};
 use std::path::Path;
-use std::{fs, fs::File};
+#[cfg(not(target_os = "wasi"))]
+use std::time::{Duration, Instant};
+use std::{fmt, fs, fs::File, ops};
 
 #[cfg(feature = "async-tokio")]
 use futures::{ready, Stream};
@@ -71,15 +73,121 @@ use std::task::Poll;
 #[cfg(feature = "async-tokio")]
 use tokio::io::unix::AsyncFd;
 
+#[cfg(feature = "async-tokio")]
+pub use async_pin::AsyncPin;
+pub use config::{ConfigStep, PinConfig};
+#[cfg(not(target_os = "wasi"))]
+pub use debounce::DebouncedPoller;
+#[cfg(feature = "async-tokio")]
+pub use debounce::DebouncedStream;
+pub use debounce::{DebounceConfig, Debouncer};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use dispatch::{Dispatcher, RegistrationId};
 pub use error::Error;
+#[cfg(not(target_os = "wasi"))]
+pub use events::{PinEvents, UntilLevel};
+#[cfg(feature = "async-tokio")]
+pub use fallback::FallbackValueStream;
+#[cfg(not(target_os = "wasi"))]
+pub use fallback::{FallbackPoller, SampleRate};
+#[cfg(not(target_os = "wasi"))]
+pub use group::PinGroup;
+#[cfg(not(target_os = "wasi"))]
+pub use handle::PinHandle;
+#[cfg(feature = "async-tokio")]
+pub use hub::PinEventHub;
+pub use led::{Led, LedTrigger, NetdevEvent};
+#[cfg(all(
+    feature = "mio-evented",
+    any(target_os = "linux", target_os = "android")
+))]
+pub use mio_registry::MioPinRegistry;
+pub use open_drain::{OpenDrainPin, OpenSourcePin};
+#[cfg(not(target_os = "wasi"))]
+pub use output::{Blink, OutputPin};
+#[cfg(feature = "async-tokio")]
+pub use pin_set::{PinSetHandle, PinSetStream};
+pub use pwm::{PwmChannel, PwmExport, PwmPolarity};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use rt::{RealtimeConfig, RealtimeError, SchedPolicy};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use schedule::{Scheduler, TransitionReport};
+#[cfg(not(target_os = "wasi"))]
+pub use soft_pwm::{SoftPwm, SoftPwmChannel};
+#[cfg(not(target_os = "wasi"))]
+pub use storm::{GuardEvent, StormConfig, StormDetector, StormGuard};
+#[cfg(feature = "async-tokio")]
+pub use stream_ext::{AsLevel, EdgeFilter, PinStreamExt, Throttle, Timeout};
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use uring::UringPins;
+#[cfg(not(target_os = "wasi"))]
+pub use waveform::{Segment, Waveform, WaveformPlayer};
 
+#[cfg(feature = "async-tokio")]
+mod async_pin;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod bench;
+mod config;
+mod debounce;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod dispatch;
 mod error;
+#[cfg(not(target_os = "wasi"))]
+mod events;
+#[cfg(not(target_os = "wasi"))]
+mod fallback;
+#[cfg(not(target_os = "wasi"))]
+mod group;
+#[cfg(not(target_os = "wasi"))]
+mod handle;
+#[cfg(feature = "async-tokio")]
+mod hub;
+mod led;
+#[cfg(all(
+    feature = "mio-evented",
+    any(target_os = "linux", target_os = "android")
+))]
+mod mio_registry;
+mod open_drain;
+#[cfg(not(target_os = "wasi"))]
+mod output;
+#[cfg(feature = "async-tokio")]
+mod pin_set;
+mod pwm;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod rt;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod schedule;
+#[cfg(not(target_os = "wasi"))]
+mod soft_pwm;
+#[cfg(not(target_os = "wasi"))]
+mod storm;
+#[cfg(feature = "async-tokio")]
+mod stream_ext;
+mod sysfs;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+#[cfg(feature = "async-tokio")]
+mod watch;
+#[cfg(not(target_os = "wasi"))]
+mod waveform;
+#[cfg(not(target_os = "wasi"))]
+mod worker;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug)]
 pub struct Pin {
     pin_num: u64,
+    verify: bool,
+}
+
+impl PartialEq for Pin {
+    fn eq(&self, other: &Pin) -> bool {
+        self.pin_num == other.pin_num
+    }
 }
 
+impl Eq for Pin {}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     In,
@@ -96,6 +204,59 @@ pub enum Edge {
     BothEdges,
 }
 
+/// The logical level of a pin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Low,
+    High,
+}
+
+impl From<u8> for Level {
+    /// Convert a pin value, where 0 is low and anything else is high
+    fn from(value: u8) -> Level {
+        match value {
+            0 => Level::Low,
+            _ => Level::High,
+        }
+    }
+}
+
+impl From<Level> for u8 {
+    fn from(level: Level) -> u8 {
+        match level {
+            Level::Low => 0,
+            Level::High => 1,
+        }
+    }
+}
+
+impl ops::Not for Level {
+    type Output = Level;
+
+    fn not(self) -> Level {
+        match self {
+            Level::Low => Level::High,
+            Level::High => Level::Low,
+        }
+    }
+}
+
+/// An interrupt observed on a pin
+#[cfg(not(target_os = "wasi"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PinEvent {
+    /// The pin on which the interrupt occurred
+    pub pin: Pin,
+    /// The value of the pin when the interrupt was handled
+    ///
+    /// As with `PinPoller::poll`, this is read as soon as the
+    /// interrupt is handled in userspace, so the pin may have changed
+    /// again since the interrupt occurred.
+    pub level: Level,
+    /// When the interrupt was handled
+    pub timestamp: Instant,
+}
+
 #[macro_export]
 macro_rules! try_unexport {
     ($gpio:ident, $e:expr) => {
@@ -123,7 +284,7 @@ fn flush_input_from_file(dev_file: &mut File, max: usize) -> io::Result<usize> {
 }
 
 /// Get the pin value from the provided file
-#[cfg(any(target_os = "linux", target_os = "android", feature = "async-tokio"))]
+#[cfg(any(target_os = "linux", target_os = "android", feature = "mio-evented"))]
 fn get_value_from_file(dev_file: &mut File) -> Result<u8> {
     let mut s = String::with_capacity(10);
     dev_file.seek(SeekFrom::Start(0))?;
@@ -158,7 +319,46 @@ impl Pin {
     ///
     /// This function does not export the provided pin_num.
     pub fn new(pin_num: u64) -> Pin {
-        Pin { pin_num }
+        Pin {
+            pin_num,
+            verify: false,
+        }
+    }
+
+    /// Enable or disable verification of attribute writes
+    ///
+    /// Some GPIO controllers accept a write to an attribute without
+    /// error but silently keep the old setting.  With verification
+    /// enabled, `set_direction`, `set_edge`, `set_active_low` and
+    /// `set_value` read the attribute back after writing it and return
+    /// `Error::VerificationFailed` if it does not hold the value that
+    /// was written.  This costs one extra read of sysfs for each write.
+    ///
+    /// Verification is disabled by default and does not affect how
+    /// pins compare for equality.  The flag belongs to this `Pin`
+    /// value only: pins returned by `get_pin()` on `PinPoller`,
+    /// `PinHandle` and the other wrappers are created from the pin
+    /// number and do not verify writes.
+    pub fn verify_writes(mut self, verify: bool) -> Pin {
+        self.verify = verify;
+        self
+    }
+
+    /// Determines whether attribute writes are verified
+    pub fn is_verifying_writes(&self) -> bool {
+        self.verify
+    }
+
+    /// Return an error if a read back attribute does not match what was written
+    fn check_written<T: PartialEq + fmt::Debug>(&self, expected: T, actual: T) -> Result<()> {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed {
+                expected: format!("{:?}", expected),
+                actual: format!("{:?}", actual),
+            })
+        }
     }
 
     /// Create a new Pin with the provided path
@@ -339,6 +539,15 @@ impl Pin {
             },
         )?;
 
+        if self.verify {
+            // the kernel reports "out" for pins set to "high" or "low"
+            let expected = match dir {
+                Direction::In => Direction::In,
+                _ => Direction::Out,
+            };
+            self.check_written(expected, self.get_direction()?)?;
+        }
+
         Ok(())
     }
 
@@ -364,6 +573,9 @@ impl Pin {
     /// This will set the value of the pin either high or low.
     /// A 0 value will set the pin low and any other value will
     /// set the pin high (1 is typical).
+    ///
+    /// When write verification is enabled and the pin is an output,
+    /// the value is read back after it has been written.
     pub fn set_value(&self, value: u8) -> Result<()> {
         self.write_to_device_file(
             "value",
@@ -373,6 +585,12 @@ impl Pin {
             },
         )?;
 
+        // the kernel rejects writes to the value of an input, so a
+        // successful write means the pin is an output
+        if self.verify {
+            self.check_written(u8::from(value != 0), self.get_value()?)?;
+        }
+
         Ok(())
     }
 
@@ -412,6 +630,10 @@ impl Pin {
             },
         )?;
 
+        if self.verify {
+            self.check_written(edge, self.get_edge()?)?;
+        }
+
         Ok(())
     }
 
@@ -443,6 +665,10 @@ impl Pin {
             },
         )?;
 
+        if self.verify {
+            self.check_written(active_low, self.get_active_low()?)?;
+        }
+
         Ok(())
     }
 
@@ -456,6 +682,109 @@ impl Pin {
         PinPoller::new(self.pin_num)
     }
 
+    /// Block until the pin is at the provided level
+    ///
+    /// If the pin is already at `level` this returns immediately.
+    /// Otherwise the pin's edge is temporarily configured so that the
+    /// change can be waited for without polling the value; the
+    /// previous edge setting is restored before returning.  The
+    /// current level is only checked after the interrupt has been
+    /// set up, so a change which happens while this call is starting
+    /// cannot be missed.
+    ///
+    /// Returns `true` once the pin is at `level`, or `false` if
+    /// `timeout` expired first.  A `timeout` of `None` waits forever.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sysfs_gpio::{Level, Pin};
+    ///
+    /// let button = Pin::new(23);
+    /// if button.wait_for_level(Level::High, Some(Duration::from_secs(5))).unwrap() {
+    ///     println!("pressed");
+    /// }
+    /// ```
+    #[cfg(not(target_os = "wasi"))]
+    pub fn wait_for_level(&self, level: Level, timeout: Option<Duration>) -> Result<bool> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let edge = match level {
+            Level::Low => Edge::FallingEdge,
+            Level::High => Edge::RisingEdge,
+        };
+        self.with_edge(edge, |poller| loop {
+            if Level::from(poller.read_value()?) == level {
+                return Ok(true);
+            }
+            if !poller.wait_for_event(deadline)? {
+                return Ok(false);
+            }
+        })
+    }
+
+    /// Block until the provided edge occurs on the pin
+    ///
+    /// The pin's edge is temporarily set to `edge` and restored to its
+    /// previous setting before returning.  Only edges which happen
+    /// after this call has been made are reported.
+    ///
+    /// Returns `true` if the edge occurred, or `false` if `timeout`
+    /// expired first.  A `timeout` of `None` waits forever.
+    #[cfg(not(target_os = "wasi"))]
+    pub fn wait_for_edge(&self, edge: Edge, timeout: Option<Duration>) -> Result<bool> {
+        if edge == Edge::NoInterrupt {
+            return Err(Error::InvalidArgument(
+                "cannot wait for Edge::NoInterrupt".to_owned(),
+            ));
+        }
+        let deadline = timeout.map(|t| Instant::now() + t);
+        self.with_edge(edge, |poller| {
+            poller.read_value()?;
+            poller.wait_for_event(deadline)
+        })
+    }
+
+    /// Run a closure with a poller while the pin's edge is set to `edge`
+    #[cfg(not(target_os = "wasi"))]
+    fn with_edge<T, F: FnOnce(&mut PinPoller) -> Result<T>>(&self, edge: Edge, f: F) -> Result<T> {
+        let previous = self.get_edge()?;
+        self.set_edge(edge)?;
+        let result = self.get_poller().and_then(|mut poller| f(&mut poller));
+        let restored = self.set_edge(previous);
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    /// Get a blocking iterator over the interrupts of this pin
+    ///
+    /// You should probably call `set_edge()` before using this.  See
+    /// `PinEvents` for details.
+    #[cfg(not(target_os = "wasi"))]
+    pub fn events(&self) -> Result<PinEvents> {
+        self.get_poller()?.into_events()
+    }
+
+    /// Get a PinHandle object for this pin
+    ///
+    /// The handle keeps the value file of the pin open for fast
+    /// repeated reads and writes of the value.
+    #[cfg(not(target_os = "wasi"))]
+    pub fn get_handle(&self) -> Result<PinHandle> {
+        PinHandle::new(self.pin_num)
+    }
+
+    /// Get a FallbackPoller object for this pin
+    ///
+    /// This sets the pin's edge to `edge` and behaves like
+    /// `get_poller()`, except that if the pin turns out not to support
+    /// interrupts its value is sampled at `rate` instead.
+    #[cfg(not(target_os = "wasi"))]
+    pub fn get_fallback_poller(&self, edge: Edge, rate: SampleRate) -> Result<FallbackPoller> {
+        FallbackPoller::new(*self, edge, rate)
+    }
+
     /// Get an AsyncPinPoller object for this pin
     ///
     /// The async pin poller object can be used with the `mio` crate. You should probably call
@@ -493,6 +822,47 @@ impl Pin {
     pub fn get_value_stream(&self) -> Result<PinValueStream> {
         Ok(PinValueStream(PinStream::init(*self)?))
     }
+
+    /// Get a Stream of pin values which falls back to sampling
+    ///
+    /// This sets the pin's edge to `edge` and behaves like
+    /// `get_value_stream()`, except that if the pin turns out not to
+    /// support interrupts its value is sampled at `rate` instead.
+    ///
+    /// This method is only available when the `async-tokio` crate feature is enabled.
+    #[cfg(feature = "async-tokio")]
+    pub fn get_fallback_value_stream(
+        &self,
+        edge: Edge,
+        rate: SampleRate,
+    ) -> Result<FallbackValueStream> {
+        FallbackValueStream::new(*self, edge, rate)
+    }
+
+    /// Get a watch channel tracking the current level of this pin
+    ///
+    /// The receiver starts out with the current level of the pin and
+    /// is only notified when the level actually changes.  The level is
+    /// kept up to date by a task spawned on the current tokio runtime,
+    /// which stops once every receiver has been dropped.  If reading
+    /// the pin fails, the task stops and receivers see the channel
+    /// closed.  You should probably call `set_edge(Edge::BothEdges)`
+    /// before using this.
+    ///
+    /// This method must be called from within a tokio runtime.  It is
+    /// only available when the `async-tokio` crate feature is enabled.
+    #[cfg(feature = "async-tokio")]
+    pub fn watch(&self) -> Result<tokio::sync::watch::Receiver<Level>> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|err| Error::Io(io::Error::other(err)))?;
+        // Deliver the first readiness event so that a change between
+        // reading the initial level and the first poll is not missed.
+        let mut stream =
+            PinValueStream(PinStream::init_with_first_event(self.get_async_poller()?)?);
+        let (tx, rx) = tokio::sync::watch::channel(Level::from(stream.get_value()?));
+        runtime.spawn(watch::publish_levels(stream, tx));
+        Ok(rx)
+    }
 }
 
 #[test]
@@ -508,6 +878,30 @@ fn extract_pin_fom_path_test() {
     let err2 = Pin::extract_pin_from_path("/sys/class/gpio/gpioSDS");
     assert!(err2.is_err());
 }
+
+#[test]
+fn level_conversion_test() {
+    assert_eq!(Level::Low, Level::from(0));
+    assert_eq!(Level::High, Level::from(1));
+    assert_eq!(Level::High, Level::from(42));
+    assert_eq!(1u8, u8::from(Level::High));
+    assert_eq!(Level::Low, !Level::High);
+}
+
+#[test]
+fn verify_writes_test() {
+    let pin = Pin::new(5).verify_writes(true);
+    assert!(pin.is_verifying_writes());
+    assert_eq!(Pin::new(5), pin);
+    assert!(pin.check_written(Edge::BothEdges, Edge::BothEdges).is_ok());
+    match pin.check_written(Edge::BothEdges, Edge::NoInterrupt) {
+        Err(Error::VerificationFailed { expected, actual }) => {
+            assert_eq!("BothEdges", expected);
+            assert_eq!("NoInterrupt", actual);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
 #[cfg(not(target_os = "wasi"))]
 #[derive(Debug)]
 pub struct PinPoller {
@@ -548,6 +942,20 @@ impl PinPoller {
         Err(Error::Unsupported("PinPoller".into()))
     }
 
+    /// Turn this poller into a blocking iterator over interrupts
+    ///
+    /// See `PinEvents` for details.
+    pub fn into_events(self) -> Result<PinEvents> {
+        PinEvents::new(self)
+    }
+
+    /// Wrap this poller so that it only reports settled level changes
+    ///
+    /// See `DebouncedPoller` for details.
+    pub fn debounce(self, config: DebounceConfig) -> Result<DebouncedPoller> {
+        DebouncedPoller::new(self, config)
+    }
+
     /// Block until an interrupt occurs
     ///
     /// This call will block until an interrupt occurs.  The types
@@ -587,11 +995,54 @@ impl PinPoller {
     pub fn poll(&mut self, _timeout_ms: isize) -> Result<Option<u8>> {
         Err(Error::Unsupported("PinPoller".into()))
     }
+
+    /// Read the value of the pin, acknowledging any pending interrupt
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn read_value(&mut self) -> Result<u8> {
+        get_value_from_file(&mut self.devfile)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn read_value(&mut self) -> Result<u8> {
+        Err(Error::Unsupported("PinPoller".into()))
+    }
+
+    /// Wait for an interrupt without first flushing the value file
+    ///
+    /// Unlike `poll()`, an interrupt which occurred since the value was
+    /// last read is reported immediately rather than discarded.
+    /// Returns `false` if `deadline` passed without an interrupt.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn wait_for_event(&mut self, deadline: Option<Instant>) -> Result<bool> {
+        let mut events = [EpollEvent::empty()];
+        loop {
+            let timeout = match deadline {
+                None => EpollTimeout::NONE,
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    // round up so we do not wake just before the deadline
+                    let ms = remaining.as_micros().div_ceil(1000);
+                    EpollTimeout::try_from(ms).map_err(|err| Error::Io(io::Error::other(err)))?
+                }
+            };
+            match self.epoll.wait(&mut events, timeout)? {
+                0 if deadline.is_some_and(|d| Instant::now() < d) => continue,
+                0 => return Ok(false),
+                _ => return Ok(true),
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn wait_for_event(&mut self, _deadline: Option<Instant>) -> Result<bool> {
+        Err(Error::Unsupported("PinPoller".into()))
+    }
 }
 
 #[cfg(feature = "mio-evented")]
 #[derive(Debug)]
 pub struct AsyncPinPoller {
+    pin_num: u64,
     devfile: File,
 }
 
@@ -599,7 +1050,23 @@ pub struct AsyncPinPoller {
 impl AsyncPinPoller {
     fn new(pin_num: u64) -> Result<Self> {
         let devfile = File::open(format!("/sys/class/gpio/gpio{}/value", pin_num))?;
-        Ok(AsyncPinPoller { devfile })
+        Ok(AsyncPinPoller { pin_num, devfile })
+    }
+
+    /// Get the pin associated with this AsyncPinPoller
+    ///
+    /// Note that this will be a new Pin object with the
+    /// proper pin number.
+    pub fn get_pin(&self) -> Pin {
+        Pin::new(self.pin_num)
+    }
+
+    /// Read the value of the pin (0 or 1)
+    ///
+    /// Reading the value also acknowledges the interrupt, so this
+    /// should be called each time the poller is reported ready.
+    pub fn read_value(&mut self) -> Result<u8> {
+        get_value_from_file(&mut self.devfile)
     }
 }
 
@@ -649,6 +1116,17 @@ impl PinStream {
             skipped_first_event: false,
         })
     }
+
+    /// Create a stream which also yields the readiness reported on registration
+    ///
+    /// Takes an already opened poller so that callers can open the
+    /// value file off the executor.
+    fn init_with_first_event(poller: AsyncPinPoller) -> Result<Self> {
+        Ok(PinStream {
+            evented: AsyncFd::new(poller)?,
+            skipped_first_event: true,
+        })
+    }
 }
 
 #[cfg(feature = "async-tokio")]
@@ -680,6 +1158,13 @@ impl PinValueStream {
     fn get_value(&mut self) -> Result<u8> {
         get_value_from_file(&mut self.0.evented.get_mut().devfile)
     }
+
+    /// Wrap this stream so that it only yields settled level changes
+    ///
+    /// See `DebouncedStream` for details.
+    pub fn debounce(self, config: DebounceConfig) -> Result<DebouncedStream<PinValueStream>> {
+        DebouncedStream::from_value_stream(self, config)
+    }
 }
 
 #[cfg(feature = "async-tokio")]
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use mio::event::{Event, Source};
+use mio::{Interest, Registry, Token};
+
+use crate::{AsyncPinPoller, Error, Level, Pin, Result};
+
+/// Assigns mio `Token`s to pins and maps events back to pin levels
+///
+/// Each registered pin gets an `AsyncPinPoller` registered for
+/// `Interest::PRIORITY`, which is how the kernel signals a GPIO
+/// interrupt.  When the `mio::Poll` reports an event, pass it to
+/// `handle_event` to find out which pin fired and read its value.
+///
+/// Tokens are allocated sequentially from the first token given on
+/// creation, so choose it to keep clear of any tokens the application
+/// uses for its own sources.
+///
+/// This type is only available when the `mio-evented` crate feature
+/// is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use mio::{Events, Poll};
+/// use sysfs_gpio::{Edge, MioPinRegistry, Pin};
+///
+/// # fn run(mut poll: Poll) -> sysfs_gpio::Result<()> {
+/// let mut pins = MioPinRegistry::new();
+/// for num in 20..24 {
+///     let pin = Pin::new(num);
+///     pin.set_edge(Edge::BothEdges)?;
+///     pins.register(poll.registry(), pin)?;
+/// }
+/// let mut events = Events::with_capacity(16);
+/// loop {
+///     poll.poll(&mut events, None)?;
+///     for event in events.iter() {
+///         if let Some(result) = pins.handle_event(event) {
+///             let (pin, level) = result?;
+///             println!("{} -> {:?}", pin.get_pin(), level);
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MioPinRegistry {
+    pollers: HashMap<Token, AsyncPinPoller>,
+    next_token: usize,
+}
+
+impl Default for MioPinRegistry {
+    fn default() -> Self {
+        MioPinRegistry::new()
+    }
+}
+
+impl MioPinRegistry {
+    /// Create a registry allocating tokens from `Token(0)`
+    pub fn new() -> MioPinRegistry {
+        MioPinRegistry::with_first_token(Token(0))
+    }
+
+    /// Create a registry allocating tokens from `first`
+    pub fn with_first_token(first: Token) -> MioPinRegistry {
+        MioPinRegistry {
+            pollers: HashMap::new(),
+            next_token: first.0,
+        }
+    }
+
+    /// Register `pin` with the mio registry, returning its token
+    ///
+    /// You should probably call `set_edge()` on the pin before
+    /// registering it.
+    pub fn register(&mut self, registry: &Registry, pin: Pin) -> Result<Token> {
+        let mut poller = pin.get_async_poller()?;
+        // acknowledge any interrupt which is already pending
+        poller.read_value()?;
+        let token = Token(self.next_token);
+        poller.register(registry, token, Interest::PRIORITY)?;
+        self.next_token += 1;
+        self.pollers.insert(token, poller);
+        Ok(token)
+    }
+
+    /// Deregister the pin with the provided token, returning the pin
+    pub fn deregister(&mut self, registry: &Registry, token: Token) -> Result<Pin> {
+        match self.pollers.remove(&token) {
+            Some(mut poller) => {
+                poller.deregister(registry)?;
+                Ok(poller.get_pin())
+            }
+            None => Err(Error::InvalidArgument(format!(
+                "no pin registered with {:?}",
+                token
+            ))),
+        }
+    }
+
+    /// Get the pin registered with the provided token
+    pub fn get_pin(&self, token: Token) -> Option<Pin> {
+        self.pollers.get(&token).map(|poller| poller.get_pin())
+    }
+
+    /// Get the token of a registered pin
+    pub fn get_token(&self, pin: Pin) -> Option<Token> {
+        self.pollers
+            .iter()
+            .find(|(_, poller)| poller.get_pin() == pin)
+            .map(|(token, _)| *token)
+    }
+
+    /// Map an event to the pin which fired and its current level
+    ///
+    /// Returns `None` if the event's token does not belong to a pin in
+    /// this registry.
+    pub fn handle_event(&mut self, event: &Event) -> Option<Result<(Pin, Level)>> {
+        let poller = self.pollers.get_mut(&event.token())?;
+        Some(
+            poller
+                .read_value()
+                .map(|value| (poller.get_pin(), Level::from(value))),
+        )
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{Direction, Level, Pin, Result};
+
+/// Open-drain output emulated on a push-pull pin
+///
+/// The sysfs interface has no open-drain mode, so it is emulated by
+/// switching the direction of the pin: the line is driven low by
+/// setting the direction to `Direction::Low` and released by setting
+/// it to `Direction::In`, leaving an external pull-up to bring it
+/// high.  The pin is never driven high, so several devices may share
+/// the line as a wired-AND bus (e.g. I2C).
+///
+/// The kernel applies "low" to the physical line regardless of the
+/// "active_low" setting, and the pin is set to active high when the
+/// wrapper is created so that `get_level` reports the physical level
+/// of the bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenDrainPin {
+    pin: Pin,
+}
+
+impl OpenDrainPin {
+    /// Wrap an exported pin, releasing the line
+    pub fn new(pin: Pin) -> Result<OpenDrainPin> {
+        pin.set_direction(Direction::In)?;
+        pin.set_active_low(false)?;
+        Ok(OpenDrainPin { pin })
+    }
+
+    /// Get the underlying Pin
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Drive the line low
+    pub fn set_low(&self) -> Result<()> {
+        self.pin.set_direction(Direction::Low)
+    }
+
+    /// Release the line, letting it float high
+    pub fn release(&self) -> Result<()> {
+        self.pin.set_direction(Direction::In)
+    }
+
+    /// Drive the line low for `Level::Low` or release it for `Level::High`
+    pub fn set_level(&self, level: Level) -> Result<()> {
+        match level {
+            Level::Low => self.set_low(),
+            Level::High => self.release(),
+        }
+    }
+
+    /// Determines whether the line is currently being driven low
+    pub fn is_driving(&self) -> Result<bool> {
+        Ok(self.pin.get_direction()? != Direction::In)
+    }
+
+    /// Read the level of the line
+    ///
+    /// While the line is released this is the level of the bus, which
+    /// may be held low by another device.
+    pub fn get_level(&self) -> Result<Level> {
+        Ok(Level::from(self.pin.get_value()?))
+    }
+}
+
+/// Open-source output emulated on a push-pull pin
+///
+/// This is the mirror image of `OpenDrainPin`: the line is driven high
+/// by setting the direction to `Direction::High` and released by
+/// setting it to `Direction::In`, leaving an external pull-down to
+/// bring it low.  The pin is never driven low, so several devices may
+/// share the line as a wired-OR bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenSourcePin {
+    pin: Pin,
+}
+
+impl OpenSourcePin {
+    /// Wrap an exported pin, releasing the line
+    pub fn new(pin: Pin) -> Result<OpenSourcePin> {
+        pin.set_direction(Direction::In)?;
+        pin.set_active_low(false)?;
+        Ok(OpenSourcePin { pin })
+    }
+
+    /// Get the underlying Pin
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Drive the line high
+    pub fn set_high(&self) -> Result<()> {
+        self.pin.set_direction(Direction::High)
+    }
+
+    /// Release the line, letting it float low
+    pub fn release(&self) -> Result<()> {
+        self.pin.set_direction(Direction::In)
+    }
+
+    /// Drive the line high for `Level::High` or release it for `Level::Low`
+    pub fn set_level(&self, level: Level) -> Result<()> {
+        match level {
+            Level::High => self.set_high(),
+            Level::Low => self.release(),
+        }
+    }
+
+    /// Determines whether the line is currently being driven high
+    pub fn is_driving(&self) -> Result<bool> {
+        Ok(self.pin.get_direction()? != Direction::In)
+    }
+
+    /// Read the level of the line
+    ///
+    /// While the line is released this is the level of the bus, which
+    /// may be held high by another device.
+    pub fn get_level(&self) -> Result<Level> {
+        Ok(Level::from(self.pin.get_value()?))
+    }
+}
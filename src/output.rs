@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::worker::Worker;
+use crate::{Direction, Error, Level, Pin, PinHandle, Result};
+
+/// An output pin which remembers the level last written to it
+///
+/// Because the level is tracked in userspace, `toggle` does not need
+/// to read the value back from sysfs first.  This assumes that
+/// nothing else writes to the pin while the `OutputPin` is in use.
+///
+/// `OutputPin` is cheap to clone; clones share the same value file
+/// and tracked level.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Level, OutputPin, Pin};
+///
+/// let led = OutputPin::new(Pin::new(127), Level::Low).unwrap();
+/// led.toggle().unwrap();
+/// led.pulse(Level::Low, Duration::from_millis(100)).unwrap();
+/// let blink = led
+///     .blink(Duration::from_millis(200), Duration::from_millis(800), 10)
+///     .unwrap();
+/// blink.join().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OutputPin {
+    inner: Arc<Mutex<Output>>,
+}
+
+#[derive(Debug)]
+struct Output {
+    handle: PinHandle,
+    level: Level,
+}
+
+impl Output {
+    fn set_level(&mut self, level: Level) -> Result<()> {
+        self.handle.set_value(level.into())?;
+        self.level = level;
+        Ok(())
+    }
+}
+
+impl OutputPin {
+    /// Configure an exported pin as an output with the provided initial level
+    ///
+    /// The direction is set to `Direction::High` or `Direction::Low`
+    /// so the pin does not glitch while being switched to an output.
+    pub fn new(pin: Pin, initial: Level) -> Result<OutputPin> {
+        // "high" and "low" set the physical level, ignoring active_low
+        let physical = match pin.get_active_low()? {
+            true => !initial,
+            false => initial,
+        };
+        pin.set_direction(match physical {
+            Level::Low => Direction::Low,
+            Level::High => Direction::High,
+        })?;
+        let handle = pin.get_handle()?;
+        Ok(OutputPin {
+            inner: Arc::new(Mutex::new(Output {
+                handle,
+                level: initial,
+            })),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Output>> {
+        self.inner
+            .lock()
+            .map_err(|_| Error::Unexpected("OutputPin lock poisoned".to_owned()))
+    }
+
+    /// Get the underlying Pin
+    pub fn get_pin(&self) -> Result<Pin> {
+        Ok(self.lock()?.handle.get_pin())
+    }
+
+    /// Get the level last written to the pin
+    pub fn get_level(&self) -> Result<Level> {
+        Ok(self.lock()?.level)
+    }
+
+    /// Set the level of the pin
+    pub fn set_level(&self, level: Level) -> Result<()> {
+        self.lock()?.set_level(level)
+    }
+
+    /// Invert the level of the pin, returning the new level
+    pub fn toggle(&self) -> Result<Level> {
+        let mut output = self.lock()?;
+        let level = !output.level;
+        output.set_level(level)?;
+        Ok(level)
+    }
+
+    /// Hold the pin at `level` for `duration`, then restore the previous level
+    ///
+    /// This blocks the calling thread for the duration of the pulse.
+    pub fn pulse(&self, level: Level, duration: Duration) -> Result<()> {
+        let mut output = self.lock()?;
+        let previous = output.level;
+        output.set_level(level)?;
+        thread::sleep(duration);
+        output.set_level(previous)
+    }
+
+    /// Blink the pin `count` times on a background thread
+    ///
+    /// Each blink drives the pin high for `on` and then low for
+    /// `off`.  This call returns immediately; the blinking is stopped
+    /// early by calling `stop()` on the returned handle or by dropping
+    /// it.
+    pub fn blink(&self, on: Duration, off: Duration, count: u32) -> Result<Blink> {
+        let output = self.clone();
+        let worker = Worker::spawn("sysfs-gpio-blink", (), move |blink| {
+            for _ in 0..count {
+                for &(level, duration) in &[(Level::High, on), (Level::Low, off)] {
+                    output.set_level(level)?;
+                    if !blink.sleep(duration) {
+                        return output.set_level(Level::Low);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+        Ok(Blink { worker })
+    }
+}
+
+/// Handle on a blink started with `OutputPin::blink`
+///
+/// Dropping the handle stops the blinking and leaves the pin low.
+#[derive(Debug)]
+pub struct Blink {
+    worker: Worker<()>,
+}
+
+impl Blink {
+    /// Stop blinking, leaving the pin low
+    pub fn stop(mut self) -> Result<()> {
+        self.worker.stop()
+    }
+
+    /// Wait for all blinks to complete
+    pub fn join(mut self) -> Result<()> {
+        self.worker.join()
+    }
+
+    /// Determines whether the blinking has completed
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+}
@@ -0,0 +1,272 @@
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures::Stream;
+use tokio::sync::mpsc;
+
+use crate::{Error, Level, Pin, PinEvent, PinValueStream, Result};
+
+enum Command<S> {
+    Insert(Pin, S),
+    Remove(Pin),
+}
+
+/// A single stream of the interrupts on a changing set of pins
+///
+/// Each item is a `PinEvent` naming the pin which fired and its value
+/// when the interrupt was handled.  By default each pin is read
+/// through its own `PinValueStream`, but any stream of pin values can
+/// be used.  Pins can be added and removed
+/// directly between polls, or from other tasks through a
+/// `PinSetHandle` while the stream is being consumed.  Pins are polled
+/// in turn so that a busy pin cannot starve the others.
+///
+/// A pin whose stream of values ends is removed from the set.  The
+/// set itself never ends on its own; while it is empty it simply
+/// waits for pins to be added.
+///
+/// This type is only available when the `async-tokio` crate feature
+/// is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use sysfs_gpio::{Pin, PinSetStream};
+///
+/// # async fn run() -> sysfs_gpio::Result<()> {
+/// let mut inputs = PinSetStream::new();
+/// for num in 20..28 {
+///     inputs.insert(Pin::new(num))?;
+/// }
+/// let handle = inputs.handle();
+/// while let Some(event) = inputs.next().await {
+///     let event = event?;
+///     println!("{} -> {:?}", event.pin.get_pin(), event.level);
+///     if event.pin.get_pin() == 20 {
+///         handle.insert(Pin::new(30))?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct PinSetStream<S = PinValueStream> {
+    streams: Vec<(Pin, S)>,
+    next: usize,
+    tx: mpsc::UnboundedSender<Command<S>>,
+    rx: mpsc::UnboundedReceiver<Command<S>>,
+}
+
+/// Adds pins to and removes pins from a `PinSetStream` from elsewhere
+pub struct PinSetHandle<S = PinValueStream> {
+    tx: mpsc::UnboundedSender<Command<S>>,
+}
+
+impl<S> Clone for PinSetHandle<S> {
+    fn clone(&self) -> Self {
+        PinSetHandle {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl PinSetHandle {
+    /// Add `pin` to the set, replacing it if it is already present
+    ///
+    /// The pin's value stream is opened immediately, so this must be
+    /// called from within a tokio runtime.  The pin is picked up the
+    /// next time the set is polled.
+    pub fn insert(&self, pin: Pin) -> Result<()> {
+        let stream = pin.get_value_stream()?;
+        self.insert_stream(pin, stream)
+    }
+}
+
+impl<S> PinSetHandle<S> {
+    /// Add `pin` to the set with the stream of values `stream`
+    ///
+    /// The stream is picked up the next time the set is polled.
+    pub fn insert_stream(&self, pin: Pin, stream: S) -> Result<()> {
+        self.send(Command::Insert(pin, stream))
+    }
+
+    /// Remove `pin` from the set the next time the set is polled
+    pub fn remove(&self, pin: Pin) -> Result<()> {
+        self.send(Command::Remove(pin))
+    }
+
+    fn send(&self, command: Command<S>) -> Result<()> {
+        self.tx
+            .send(command)
+            .map_err(|_| Error::Unexpected("PinSetStream has been dropped".to_owned()))
+    }
+}
+
+impl<S> Default for PinSetStream<S> {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        PinSetStream {
+            streams: Vec::new(),
+            next: 0,
+            tx,
+            rx,
+        }
+    }
+}
+
+impl PinSetStream {
+    /// Create an empty set
+    pub fn new() -> PinSetStream {
+        PinSetStream::default()
+    }
+
+    /// Add `pin` to the set, replacing it if it is already present
+    ///
+    /// This must be called from within a tokio runtime.  You should
+    /// probably call `set_edge()` on the pin before adding it.
+    pub fn insert(&mut self, pin: Pin) -> Result<()> {
+        let stream = pin.get_value_stream()?;
+        self.insert_stream(pin, stream);
+        Ok(())
+    }
+}
+
+impl<S> PinSetStream<S> {
+    /// Get a handle for changing the set while it is being polled
+    pub fn handle(&self) -> PinSetHandle<S> {
+        PinSetHandle {
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Add `pin` to the set with the stream of values `stream`
+    ///
+    /// This replaces `pin` if it is already present.  `insert` is
+    /// this with the pin's own `PinValueStream`; other streams of pin
+    /// values, such as a `DebouncedStream`, can be added with this.
+    pub fn insert_stream(&mut self, pin: Pin, stream: S) {
+        self.remove(pin);
+        self.streams.push((pin, stream));
+    }
+
+    /// Remove `pin` from the set, returning whether it was present
+    pub fn remove(&mut self, pin: Pin) -> bool {
+        let len = self.streams.len();
+        self.streams.retain(|(p, _)| *p != pin);
+        self.streams.len() != len
+    }
+
+    /// Determines whether `pin` is in the set
+    pub fn contains(&self, pin: Pin) -> bool {
+        self.streams.iter().any(|(p, _)| *p == pin)
+    }
+
+    /// Get the pins currently in the set
+    pub fn pins(&self) -> Vec<Pin> {
+        self.streams.iter().map(|(p, _)| *p).collect()
+    }
+
+    /// Get the number of pins in the set
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Determines whether the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+}
+
+impl<S> Stream for PinSetStream<S>
+where
+    S: Stream<Item = Result<u8>> + Unpin,
+{
+    type Item = Result<PinEvent>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while let Poll::Ready(Some(command)) = this.rx.poll_recv(cx) {
+            match command {
+                Command::Insert(pin, stream) => this.insert_stream(pin, stream),
+                Command::Remove(pin) => {
+                    this.remove(pin);
+                }
+            }
+        }
+
+        let mut index = this.next;
+        let mut remaining = this.streams.len();
+        while remaining > 0 {
+            if index >= this.streams.len() {
+                index = 0;
+            }
+            let (pin, ref mut stream) = this.streams[index];
+            match std::pin::Pin::new(stream).poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    // start with the following pin next time
+                    this.next = index + 1;
+                    return Poll::Ready(Some(value.map(|value| PinEvent {
+                        pin,
+                        level: Level::from(value),
+                        timestamp: Instant::now(),
+                    })));
+                }
+                Poll::Ready(None) => {
+                    // the following pin moves down into this index
+                    this.streams.remove(index);
+                }
+                Poll::Pending => index += 1,
+            }
+            remaining -= 1;
+        }
+        Poll::Pending
+    }
+}
+
+#[test]
+fn pin_set_stream_test() {
+    use futures::channel::mpsc::unbounded;
+    use futures::{FutureExt, StreamExt};
+
+    fn next_pin<S: Stream<Item = Result<u8>> + Unpin>(set: &mut PinSetStream<S>) -> Option<Pin> {
+        set.next().now_or_never().map(|e| e.unwrap().unwrap().pin)
+    }
+
+    let mut set = PinSetStream::default();
+    let (tx1, rx1) = unbounded::<Result<u8>>();
+    let (tx2, rx2) = unbounded();
+    let handle = set.handle();
+    set.insert_stream(Pin::new(1), rx1);
+    set.insert_stream(Pin::new(2), rx2);
+    for value in [1, 0, 1] {
+        tx1.unbounded_send(Ok(value)).unwrap();
+        tx2.unbounded_send(Ok(value)).unwrap();
+    }
+
+    // a busy pin does not starve the others
+    assert_eq!(Some(Pin::new(1)), next_pin(&mut set));
+    assert_eq!(Some(Pin::new(2)), next_pin(&mut set));
+    assert_eq!(Some(Pin::new(1)), next_pin(&mut set));
+    assert!(set.remove(Pin::new(1)));
+    assert!(!set.contains(Pin::new(1)));
+    assert_eq!(Some(Pin::new(2)), next_pin(&mut set));
+    assert_eq!(Some(Pin::new(2)), next_pin(&mut set));
+    assert_eq!(None, next_pin(&mut set));
+
+    // pins added through a handle are picked up on the next poll
+    let (tx3, rx3) = unbounded();
+    handle.insert_stream(Pin::new(3), rx3).unwrap();
+    tx3.unbounded_send(Ok(1)).unwrap();
+    assert_eq!(Some(Pin::new(3)), next_pin(&mut set));
+    assert_eq!(vec![Pin::new(2), Pin::new(3)], set.pins());
+
+    // finished streams are dropped from the set
+    drop(tx2);
+    drop(tx3);
+    assert_eq!(None, next_pin(&mut set));
+    assert!(set.is_empty());
+    drop(tx1);
+}
@@ -0,0 +1,330 @@
+use std::fs;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::sysfs::{parse_attribute, read_attribute, write_attribute, write_display};
+use crate::{Error, Result};
+
+/// The polarity of a PWM output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PwmPolarity {
+    /// The output is high for the duty cycle and low for the rest of the period
+    Normal,
+    /// The output is low for the duty cycle and high for the rest of the period
+    Inversed,
+}
+
+/// A channel of a PWM controller exposed through `/sys/class/pwm`
+///
+/// The PWM sysfs interface follows the same pattern as GPIO: a
+/// channel is exported by writing its number to the `export` file of
+/// its `pwmchipN` directory, after which it is controlled through
+/// attribute files in `pwmchipN/pwmM`.  Times are written to sysfs in
+/// nanoseconds.
+///
+/// By default channels are looked up under `/sys/class/pwm`; a
+/// different root can be given with `with_root`, mostly for testing
+/// against a fake sysfs tree.  There is deliberately no crate-wide
+/// sysfs root: `Pin` and the other GPIO types always use
+/// `/sys/class/gpio`, and the root only applies to the `PwmChannel`
+/// it is set on.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{PwmChannel, PwmPolarity};
+///
+/// // a servo on channel 0 of pwmchip0: 20ms period, 1.5ms pulse
+/// let servo = PwmChannel::new(0, 0).exported().unwrap();
+/// servo.set_polarity(PwmPolarity::Normal).unwrap();
+/// servo
+///     .configure(Duration::from_millis(20), Duration::from_micros(1500))
+///     .unwrap();
+/// servo.enable().unwrap();
+/// // the channel is unexported when `servo` is dropped
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PwmChannel {
+    root: PathBuf,
+    chip: u32,
+    channel: u32,
+}
+
+impl PwmChannel {
+    /// Create a new PwmChannel for `channel` of `pwmchip{chip}`
+    ///
+    /// This function does not export the channel.
+    pub fn new(chip: u32, channel: u32) -> PwmChannel {
+        PwmChannel {
+            root: PathBuf::from("/sys/class/pwm"),
+            chip,
+            channel,
+        }
+    }
+
+    /// Look the channel up under `root` instead of `/sys/class/pwm`
+    pub fn with_root<P: Into<PathBuf>>(mut self, root: P) -> PwmChannel {
+        self.root = root.into();
+        self
+    }
+
+    /// Get the number of the chip the channel belongs to
+    pub fn get_chip(&self) -> u32 {
+        self.chip
+    }
+
+    /// Get the number of the channel within its chip
+    pub fn get_channel(&self) -> u32 {
+        self.channel
+    }
+
+    fn chip_path(&self) -> PathBuf {
+        self.root.join(format!("pwmchip{}", self.chip))
+    }
+
+    /// Get the sysfs directory of the exported channel
+    pub fn get_path(&self) -> PathBuf {
+        self.chip_path().join(format!("pwm{}", self.channel))
+    }
+
+    fn write_to_device_file(&self, dev_file_name: &str, value: &str) -> Result<()> {
+        write_attribute(&self.get_path().join(dev_file_name), value)
+    }
+
+    fn read_from_device_file(&self, dev_file_name: &str) -> Result<String> {
+        read_attribute(&self.get_path().join(dev_file_name))
+    }
+
+    fn read_nanos(&self, dev_file_name: &str) -> Result<Duration> {
+        parse_attribute(&self.get_path().join(dev_file_name)).map(Duration::from_nanos)
+    }
+
+    fn write_nanos(&self, dev_file_name: &str, value: Duration) -> Result<()> {
+        write_display(&self.get_path().join(dev_file_name), value.as_nanos())
+    }
+
+    /// Get the number of channels provided by the chip
+    pub fn get_npwm(&self) -> Result<u32> {
+        parse_attribute(&self.chip_path().join("npwm"))
+    }
+
+    /// Determines whether the channel is exported
+    pub fn is_exported(&self) -> bool {
+        fs::metadata(self.get_path()).is_ok()
+    }
+
+    /// Export the channel
+    ///
+    /// As with `Pin::export`, the case where the channel is already
+    /// exported is not an error.
+    pub fn export(&self) -> Result<()> {
+        if !self.is_exported() {
+            write_display(&self.chip_path().join("export"), self.channel)?;
+        }
+        Ok(())
+    }
+
+    /// Unexport the channel
+    ///
+    /// If the channel is not currently exported, this returns without
+    /// error.
+    pub fn unexport(&self) -> Result<()> {
+        if self.is_exported() {
+            write_display(&self.chip_path().join("unexport"), self.channel)?;
+        }
+        Ok(())
+    }
+
+    /// Export the channel, returning a guard which unexports it when dropped
+    ///
+    /// If the channel was already exported, the guard leaves it
+    /// exported.
+    pub fn exported(&self) -> Result<PwmExport> {
+        let was_exported = self.is_exported();
+        self.export()?;
+        Ok(PwmExport {
+            channel: self.clone(),
+            unexport: !was_exported,
+        })
+    }
+
+    /// Get the period of the channel
+    pub fn get_period(&self) -> Result<Duration> {
+        self.read_nanos("period")
+    }
+
+    /// Set the period of the channel
+    ///
+    /// The kernel rejects a period shorter than the current duty
+    /// cycle; use `configure` to change both together.
+    pub fn set_period(&self, period: Duration) -> Result<()> {
+        self.write_nanos("period", period)
+    }
+
+    /// Get the active time of each period
+    pub fn get_duty_cycle(&self) -> Result<Duration> {
+        self.read_nanos("duty_cycle")
+    }
+
+    /// Set the active time of each period
+    ///
+    /// The duty cycle must not be longer than the period.
+    pub fn set_duty_cycle(&self, duty_cycle: Duration) -> Result<()> {
+        self.write_nanos("duty_cycle", duty_cycle)
+    }
+
+    /// Set the period and duty cycle together
+    ///
+    /// The attributes are written in whichever order keeps the duty
+    /// cycle within the period at every step.
+    pub fn configure(&self, period: Duration, duty_cycle: Duration) -> Result<()> {
+        if duty_cycle > period {
+            return Err(Error::InvalidArgument(format!(
+                "duty cycle {:?} is longer than period {:?}",
+                duty_cycle, period
+            )));
+        }
+        match self.get_duty_cycle()? > period {
+            true => {
+                self.set_duty_cycle(duty_cycle)?;
+                self.set_period(period)
+            }
+            false => {
+                self.set_period(period)?;
+                self.set_duty_cycle(duty_cycle)
+            }
+        }
+    }
+
+    /// Get the polarity of the channel
+    pub fn get_polarity(&self) -> Result<PwmPolarity> {
+        match self.read_from_device_file("polarity")?.trim() {
+            "normal" => Ok(PwmPolarity::Normal),
+            "inversed" => Ok(PwmPolarity::Inversed),
+            other => Err(Error::Unexpected(format!(
+                "polarity file contents {}",
+                other
+            ))),
+        }
+    }
+
+    /// Set the polarity of the channel
+    ///
+    /// Many controllers only allow the polarity to be changed while
+    /// the channel is disabled.
+    pub fn set_polarity(&self, polarity: PwmPolarity) -> Result<()> {
+        self.write_to_device_file(
+            "polarity",
+            match polarity {
+                PwmPolarity::Normal => "normal",
+                PwmPolarity::Inversed => "inversed",
+            },
+        )
+    }
+
+    /// Determines whether the channel is enabled
+    pub fn is_enabled(&self) -> Result<bool> {
+        match self.read_from_device_file("enable")?.trim() {
+            "1" => Ok(true),
+            "0" => Ok(false),
+            other => Err(Error::Unexpected(format!("enable file contents {}", other))),
+        }
+    }
+
+    /// Start generating the output
+    pub fn enable(&self) -> Result<()> {
+        self.write_to_device_file("enable", "1")
+    }
+
+    /// Stop generating the output
+    pub fn disable(&self) -> Result<()> {
+        self.write_to_device_file("enable", "0")
+    }
+}
+
+/// An exported `PwmChannel` which is unexported when dropped
+///
+/// Created by `PwmChannel::exported`.  The channel's methods are
+/// available through `Deref`.
+#[derive(Debug)]
+pub struct PwmExport {
+    channel: PwmChannel,
+    unexport: bool,
+}
+
+impl PwmExport {
+    /// Keep the channel exported after the guard is dropped
+    pub fn leak(mut self) -> PwmChannel {
+        self.unexport = false;
+        self.channel.clone()
+    }
+}
+
+impl Deref for PwmExport {
+    type Target = PwmChannel;
+
+    fn deref(&self) -> &PwmChannel {
+        &self.channel
+    }
+}
+
+impl Drop for PwmExport {
+    fn drop(&mut self) {
+        if self.unexport {
+            let _ = self.channel.unexport();
+        }
+    }
+}
+
+#[cfg(test)]
+fn fake_pwm(name: &str) -> crate::sysfs::FakeSysfs {
+    let sysfs = crate::sysfs::FakeSysfs::new(name);
+    sysfs.file("pwmchip0/npwm", "2\n");
+    sysfs
+}
+
+#[test]
+fn pwm_attributes_test() {
+    let sysfs = fake_pwm("pwm-attributes");
+    let pwm = PwmChannel::new(0, 1).with_root(&sysfs.root);
+    assert_eq!(2, pwm.get_npwm().unwrap());
+    assert!(!pwm.is_exported());
+    sysfs.file("pwmchip0/pwm1/duty_cycle", "0\n");
+
+    pwm.configure(Duration::from_millis(20), Duration::from_micros(1500))
+        .unwrap();
+    assert_eq!(Duration::from_millis(20), pwm.get_period().unwrap());
+    assert_eq!("1500000", sysfs.read("pwmchip0/pwm1/duty_cycle"));
+    pwm.set_polarity(PwmPolarity::Inversed).unwrap();
+    assert_eq!(PwmPolarity::Inversed, pwm.get_polarity().unwrap());
+    pwm.enable().unwrap();
+    assert!(pwm.is_enabled().unwrap());
+    assert!(pwm
+        .configure(Duration::from_millis(1), Duration::from_millis(2))
+        .is_err());
+
+    sysfs.file("pwmchip0/pwm1/polarity", "sideways\n");
+    assert!(matches!(pwm.get_polarity(), Err(Error::Unexpected(_))));
+}
+
+#[test]
+fn pwm_export_guard_test() {
+    let sysfs = fake_pwm("pwm-export");
+    let pwm = PwmChannel::new(0, 0).with_root(&sysfs.root);
+
+    // the fake export file cannot create the channel directory, so
+    // the guard sees the channel as newly exported once it exists
+    let guard = pwm.exported().unwrap();
+    assert_eq!("0", sysfs.read("pwmchip0/export"));
+    fs::create_dir(pwm.get_path()).unwrap();
+    drop(guard);
+    assert_eq!("0", sysfs.read("pwmchip0/unexport"));
+
+    // a channel which was already exported is left alone
+    let unexport = sysfs.root.join("pwmchip0/unexport");
+    fs::remove_file(&unexport).unwrap();
+    drop(pwm.exported().unwrap());
+    assert!(fs::metadata(&unexport).is_err());
+}
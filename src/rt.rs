@@ -0,0 +1,224 @@
+use std::fmt;
+
+use nix::errno::Errno;
+use nix::libc;
+use nix::sched::{sched_setaffinity, CpuSet};
+use nix::sys::mman::{mlockall, MlockAllFlags};
+use nix::unistd::Pid;
+
+use crate::{Error, Result};
+
+/// Scheduling policy for a real-time thread
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedPolicy {
+    /// The default time-sharing policy (`SCHED_OTHER`)
+    Other,
+    /// First-in first-out real-time policy (`SCHED_FIFO`)
+    Fifo,
+    /// Round-robin real-time policy (`SCHED_RR`)
+    RoundRobin,
+}
+
+impl SchedPolicy {
+    fn as_raw(self) -> libc::c_int {
+        match self {
+            SchedPolicy::Other => libc::SCHED_OTHER,
+            SchedPolicy::Fifo => libc::SCHED_FIFO,
+            SchedPolicy::RoundRobin => libc::SCHED_RR,
+        }
+    }
+
+    /// Get the range of priorities valid for this policy
+    pub fn priority_range(self) -> Result<(i32, i32)> {
+        // SAFETY: these calls only take a policy constant and have no side effects
+        let min = unsafe { libc::sched_get_priority_min(self.as_raw()) };
+        let max = unsafe { libc::sched_get_priority_max(self.as_raw()) };
+        Errno::result(min)
+            .and(Errno::result(max))
+            .map(|_| (min, max))
+            .map_err(|errno| RealtimeError::Scheduler(errno).into())
+    }
+}
+
+/// A failure to apply a `RealtimeConfig`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RealtimeError {
+    /// The priority is outside the range allowed for the policy
+    InvalidPriority {
+        policy: SchedPolicy,
+        priority: i32,
+        min: i32,
+        max: i32,
+    },
+    /// Setting the scheduling policy and priority failed
+    Scheduler(Errno),
+    /// Setting the CPU affinity failed
+    Affinity(Errno),
+    /// Locking memory failed
+    MemoryLock(Errno),
+}
+
+impl RealtimeError {
+    /// Determines whether the failure was caused by missing privileges
+    ///
+    /// Real-time scheduling needs `CAP_SYS_NICE` (or a suitable
+    /// `RLIMIT_RTPRIO`) and locking memory needs `CAP_IPC_LOCK` (or a
+    /// large enough `RLIMIT_MEMLOCK`; `mlockall` reports exceeding it
+    /// as ENOMEM).
+    pub fn is_permission_denied(&self) -> bool {
+        match *self {
+            RealtimeError::Scheduler(errno) | RealtimeError::Affinity(errno) => {
+                errno == Errno::EPERM
+            }
+            RealtimeError::MemoryLock(errno) => errno == Errno::EPERM || errno == Errno::ENOMEM,
+            RealtimeError::InvalidPriority { .. } => false,
+        }
+    }
+}
+
+impl std::error::Error for RealtimeError {}
+
+impl fmt::Display for RealtimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RealtimeError::InvalidPriority {
+                policy,
+                priority,
+                min,
+                max,
+            } => write!(
+                f,
+                "priority {} is outside {}..={} allowed for {:?}",
+                priority, min, max, policy
+            ),
+            RealtimeError::Scheduler(errno) => write!(f, "setting scheduler failed: {}", errno),
+            RealtimeError::Affinity(errno) => write!(f, "setting CPU affinity failed: {}", errno),
+            RealtimeError::MemoryLock(errno) => write!(f, "locking memory failed: {}", errno),
+        }
+    }
+}
+
+impl From<RealtimeError> for Error {
+    fn from(e: RealtimeError) -> Error {
+        Error::Realtime(e)
+    }
+}
+
+/// Real-time tuning for a thread handling interrupts
+///
+/// Only the settings which have been set on the builder are applied.
+/// `apply` tunes the calling thread, so call it at the start of the
+/// thread running a `PinPoller` loop, or pass the configuration to
+/// `Dispatcher::with_realtime` to tune the dispatcher thread.
+///
+/// # Example
+///
+/// ```no_run
+/// use sysfs_gpio::{RealtimeConfig, SchedPolicy};
+///
+/// RealtimeConfig::new()
+///     .scheduler(SchedPolicy::Fifo, 80)
+///     .cpu_affinity(&[3])
+///     .lock_memory(true)
+///     .apply()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RealtimeConfig {
+    scheduler: Option<(SchedPolicy, i32)>,
+    cpus: Option<Vec<usize>>,
+    lock_memory: bool,
+}
+
+impl RealtimeConfig {
+    /// Create a configuration which changes nothing
+    pub fn new() -> RealtimeConfig {
+        RealtimeConfig::default()
+    }
+
+    /// Set the scheduling policy and priority
+    pub fn scheduler(mut self, policy: SchedPolicy, priority: i32) -> RealtimeConfig {
+        self.scheduler = Some((policy, priority));
+        self
+    }
+
+    /// Restrict the thread to the provided CPUs
+    pub fn cpu_affinity(mut self, cpus: &[usize]) -> RealtimeConfig {
+        self.cpus = Some(cpus.to_vec());
+        self
+    }
+
+    /// Lock all current and future memory of the process into RAM
+    ///
+    /// Note that this affects the whole process, not just the thread.
+    pub fn lock_memory(mut self, lock: bool) -> RealtimeConfig {
+        self.lock_memory = lock;
+        self
+    }
+
+    /// Apply the configuration to the calling thread
+    ///
+    /// Settings are applied in the order scheduler, CPU affinity and
+    /// memory locking, stopping at the first failure.
+    pub fn apply(&self) -> Result<()> {
+        if let Some((policy, priority)) = self.scheduler {
+            let (min, max) = policy.priority_range()?;
+            if priority < min || priority > max {
+                return Err(RealtimeError::InvalidPriority {
+                    policy,
+                    priority,
+                    min,
+                    max,
+                }
+                .into());
+            }
+            let param = libc::sched_param {
+                sched_priority: priority,
+            };
+            // pthread_setschedparam rather than sched_setscheduler, which
+            // musl does not implement; it returns the error instead of
+            // setting errno.
+            // SAFETY: param is a valid sched_param and the thread is the caller
+            let res = unsafe {
+                libc::pthread_setschedparam(libc::pthread_self(), policy.as_raw(), &param)
+            };
+            if res != 0 {
+                return Err(RealtimeError::Scheduler(Errno::from_raw(res)).into());
+            }
+        }
+        if let Some(ref cpus) = self.cpus {
+            let mut set = CpuSet::new();
+            for &cpu in cpus {
+                set.set(cpu).map_err(RealtimeError::Affinity)?;
+            }
+            sched_setaffinity(Pid::from_raw(0), &set).map_err(RealtimeError::Affinity)?;
+        }
+        if self.lock_memory {
+            mlockall(MlockAllFlags::MCL_CURRENT | MlockAllFlags::MCL_FUTURE)
+                .map_err(RealtimeError::MemoryLock)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn permission_denied_test() {
+    assert!(RealtimeError::Scheduler(Errno::EPERM).is_permission_denied());
+    assert!(!RealtimeError::Scheduler(Errno::ENOMEM).is_permission_denied());
+    assert!(!RealtimeError::Affinity(Errno::ENOMEM).is_permission_denied());
+    assert!(RealtimeError::MemoryLock(Errno::ENOMEM).is_permission_denied());
+}
+
+#[test]
+fn invalid_priority_test() {
+    let err = RealtimeConfig::new()
+        .scheduler(SchedPolicy::Fifo, 1000)
+        .apply()
+        .unwrap_err();
+    match err {
+        Error::Realtime(RealtimeError::InvalidPriority { priority, .. }) => {
+            assert_eq!(1000, priority)
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
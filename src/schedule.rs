@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::sys::time::TimeSpec;
+use nix::sys::timerfd::{ClockId, Expiration, TimerFd, TimerFlags, TimerSetTimeFlags};
+use nix::time::clock_gettime;
+
+use crate::{Error, Level, Pin, PinHandle, Result};
+
+/// What happened to one transition run by a `Scheduler`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransitionReport {
+    /// The pin which was written
+    pub pin: Pin,
+    /// The level written to the pin
+    pub level: Level,
+    /// When the transition was due, relative to the start of the run
+    pub deadline: Duration,
+    /// How long after the deadline the write completed
+    pub lateness: Duration,
+}
+
+/// Applies output changes at precise monotonic deadlines
+///
+/// Sleeping between writes, as `examples/blinky.rs` does, accumulates
+/// drift and jitter because every sleep is relative to whenever the
+/// previous write happened to finish.  A `Scheduler` instead holds a
+/// list of transitions at fixed offsets from the start of a run and
+/// waits for each one with a `timerfd` armed at an absolute
+/// `CLOCK_MONOTONIC` deadline, so a late transition does not delay
+/// the ones after it.  Outputs are written through `PinHandle`s
+/// opened beforehand.
+///
+/// Every run returns a `TransitionReport` per transition recording
+/// how late it was.  For the best results, run the scheduler on a
+/// thread tuned with `RealtimeConfig`.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Level, Pin, Scheduler};
+///
+/// let mut scheduler = Scheduler::new().unwrap();
+/// let clock = scheduler.add_output(Pin::new(17).get_handle().unwrap());
+/// scheduler.schedule(clock, Duration::ZERO, Level::High).unwrap();
+/// scheduler.schedule(clock, Duration::from_micros(1500), Level::Low).unwrap();
+/// // repeat the 1.5ms pulse every 20ms, 50 times
+/// let reports = scheduler.run(50, Duration::from_millis(20)).unwrap();
+/// let worst = reports.iter().map(|r| r.lateness).max();
+/// println!("worst lateness: {:?}", worst);
+/// ```
+#[derive(Debug)]
+pub struct Scheduler {
+    timer: TimerFd,
+    outputs: Vec<PinHandle>,
+    /// Queued transitions as (offset, output, level), sorted by offset
+    transitions: Vec<(Duration, usize, Level)>,
+}
+
+/// Expand a cycle of transitions into the deadlines of `repeat` cycles
+fn deadlines(
+    transitions: &[(Duration, usize, Level)],
+    repeat: u32,
+    period: Duration,
+) -> impl Iterator<Item = (Duration, usize, Level)> + '_ {
+    (0..repeat).flat_map(move |cycle| {
+        transitions
+            .iter()
+            .map(move |&(offset, output, level)| (period * cycle + offset, output, level))
+    })
+}
+
+impl Scheduler {
+    /// Create a scheduler with no outputs
+    pub fn new() -> Result<Scheduler> {
+        Ok(Scheduler {
+            timer: TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::TFD_CLOEXEC)?,
+            outputs: Vec::new(),
+            transitions: Vec::new(),
+        })
+    }
+
+    /// Add an output, returning the index used to schedule transitions on it
+    pub fn add_output(&mut self, handle: PinHandle) -> usize {
+        self.outputs.push(handle);
+        self.outputs.len() - 1
+    }
+
+    /// Queue a change of `output` to `level` at offset `at` from the start of a run
+    ///
+    /// Transitions at the same offset are applied in the order they
+    /// were queued.
+    pub fn schedule(&mut self, output: usize, at: Duration, level: Level) -> Result<()> {
+        if output >= self.outputs.len() {
+            return Err(Error::InvalidArgument(format!(
+                "no output with index {}",
+                output
+            )));
+        }
+        let pos = self
+            .transitions
+            .partition_point(|&(offset, _, _)| offset <= at);
+        self.transitions.insert(pos, (at, output, level));
+        Ok(())
+    }
+
+    /// Remove all queued transitions
+    pub fn clear(&mut self) {
+        self.transitions.clear();
+    }
+
+    /// Run the queued transitions once
+    pub fn run_once(&mut self) -> Result<Vec<TransitionReport>> {
+        self.run(1, Duration::ZERO)
+    }
+
+    /// Run the queued transitions `repeat` times, starting a cycle every `period`
+    ///
+    /// Every transition must fall within the period when repeating.
+    /// This blocks until the last transition has been applied.
+    pub fn run(&mut self, repeat: u32, period: Duration) -> Result<Vec<TransitionReport>> {
+        if repeat > 1 {
+            if let Some(&(last, _, _)) = self.transitions.last() {
+                if last >= period {
+                    return Err(Error::InvalidArgument(format!(
+                        "transition at {:?} does not fit in period {:?}",
+                        last, period
+                    )));
+                }
+            }
+        }
+        // grown as transitions run, since the product may not fit in memory
+        let mut reports = Vec::new();
+        let start = monotonic_now()?;
+        for (deadline, output, level) in deadlines(&self.transitions, repeat, period) {
+            self.timer.set(
+                Expiration::OneShot(TimeSpec::from(start + deadline)),
+                TimerSetTimeFlags::TFD_TIMER_ABSTIME,
+            )?;
+            loop {
+                match self.timer.wait() {
+                    Err(Errno::EINTR) => continue,
+                    res => break res?,
+                }
+            }
+            let handle = &self.outputs[output];
+            handle.set_value(level.into())?;
+            let done = monotonic_now()?;
+            reports.push(TransitionReport {
+                pin: handle.get_pin(),
+                level,
+                deadline,
+                lateness: done.saturating_sub(start + deadline),
+            });
+        }
+        Ok(reports)
+    }
+}
+
+/// Read CLOCK_MONOTONIC, the clock the timer deadlines are based on
+fn monotonic_now() -> Result<Duration> {
+    Ok(Duration::from(clock_gettime(
+        nix::time::ClockId::CLOCK_MONOTONIC,
+    )?))
+}
+
+#[test]
+fn deadlines_test() {
+    let ms = Duration::from_millis;
+    let cycle = [(ms(0), 0, Level::High), (ms(3), 1, Level::Low)];
+    let planned: Vec<_> = deadlines(&cycle, 2, ms(10)).collect();
+    assert_eq!(
+        vec![
+            (ms(0), 0, Level::High),
+            (ms(3), 1, Level::Low),
+            (ms(10), 0, Level::High),
+            (ms(13), 1, Level::Low),
+        ],
+        planned
+    );
+    assert_eq!(0, deadlines(&cycle, 0, ms(10)).count());
+}
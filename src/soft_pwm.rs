@@ -0,0 +1,263 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::worker::{Shared, Worker};
+use crate::{Error, Pin, PinHandle, Result};
+
+#[derive(Debug)]
+struct Channel {
+    id: u64,
+    handle: Arc<PinHandle>,
+    duty: f64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    channels: Vec<Channel>,
+}
+
+/// Check that a duty cycle is between 0.0 and 1.0
+fn check_duty(duty: f64) -> Result<f64> {
+    match (0.0..=1.0).contains(&duty) {
+        true => Ok(duty),
+        false => Err(Error::InvalidArgument(format!(
+            "duty cycle {} is not between 0.0 and 1.0",
+            duty
+        ))),
+    }
+}
+
+/// Get when in the period a channel with the given duty cycle goes low
+///
+/// Returns `None` for channels which stay at one level for the whole
+/// period.
+fn fall_time(period: Duration, duty: f64) -> Option<Duration> {
+    match duty {
+        d if d <= 0.0 || d >= 1.0 => None,
+        d => Some(period.mul_f64(d)),
+    }
+}
+
+/// Software PWM on ordinary output pins
+///
+/// A `SoftPwm` owns a timing thread which drives any number of
+/// channels at one shared frequency.  At the start of every period
+/// each channel with a non-zero duty cycle is driven high, and each
+/// is driven low again once its share of the period has elapsed.
+/// Periods are timed against a running deadline so that the
+/// frequency does not drift.
+///
+/// The precision is limited by the scheduling latency of the timing
+/// thread and the cost of writing to sysfs, so this is suited to
+/// dimming LEDs or driving fans at up to a few hundred hertz rather
+/// than to servos or anything needing an exact pulse width.  Where
+/// the pin supports it, prefer hardware PWM.
+///
+/// Stopping the generator, or dropping it, drives every channel low.
+///
+/// # Example
+///
+/// ```no_run
+/// use sysfs_gpio::{Direction, Pin, SoftPwm};
+///
+/// let pin = Pin::new(18);
+/// pin.export().unwrap();
+/// pin.set_direction(Direction::Low).unwrap();
+///
+/// let pwm = SoftPwm::new(200.0).unwrap();
+/// let fan = pwm.add_channel(pin, 0.25).unwrap();
+/// // later, spin the fan up
+/// fan.set_duty_cycle(0.8).unwrap();
+/// pwm.stop().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SoftPwm {
+    period: Duration,
+    worker: Worker<State>,
+}
+
+impl SoftPwm {
+    /// Start a generator running at `frequency` hertz with no channels
+    pub fn new(frequency: f64) -> Result<SoftPwm> {
+        let period = match Duration::try_from_secs_f64(1.0 / frequency) {
+            Ok(period) if !period.is_zero() => period,
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "PWM frequency {} is out of range",
+                    frequency
+                )))
+            }
+        };
+        let worker = Worker::spawn("sysfs-gpio-soft-pwm", State::default(), move |timing| {
+            run(timing, period)
+        })?;
+        Ok(SoftPwm { period, worker })
+    }
+
+    /// Get the period of the generator
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Get the frequency of the generator in hertz
+    pub fn frequency(&self) -> f64 {
+        1.0 / self.period.as_secs_f64()
+    }
+
+    /// Add a channel driving `pin` with the provided duty cycle
+    ///
+    /// The pin must already be exported and configured as an output.
+    /// The duty cycle is the fraction of each period the pin is high,
+    /// from 0.0 to 1.0.  The channel starts at the beginning of the
+    /// next period.
+    pub fn add_channel(&self, pin: Pin, duty: f64) -> Result<SoftPwmChannel> {
+        let duty = check_duty(duty)?;
+        let handle = Arc::new(pin.get_handle()?);
+        let mut control = self.worker.shared().lock();
+        let state = &mut control.state;
+        let id = state.next_id;
+        state.next_id += 1;
+        state.channels.push(Channel { id, handle, duty });
+        Ok(SoftPwmChannel {
+            id,
+            pin,
+            shared: self.worker.shared().clone(),
+        })
+    }
+
+    /// Stop the generator, leaving every channel low
+    ///
+    /// This reports the first error the timing thread hit writing to
+    /// a pin, if any.
+    pub fn stop(mut self) -> Result<()> {
+        self.worker.stop()
+    }
+}
+
+/// The body of the timing thread
+fn run(shared: &Shared<State>, period: Duration) -> Result<()> {
+    let result = run_periods(shared, period);
+    // leave the pins low whether we were stopped or hit an error
+    let idle = shared
+        .lock()
+        .state
+        .channels
+        .iter()
+        .try_for_each(|c| c.handle.set_value(0));
+    result.and(idle)
+}
+
+fn run_periods(shared: &Shared<State>, period: Duration) -> Result<()> {
+    let mut start = Instant::now();
+    loop {
+        let mut falls = Vec::new();
+        {
+            let control = shared.lock();
+            if control.stop {
+                return Ok(());
+            }
+            for channel in &control.state.channels {
+                channel.handle.set_value((channel.duty > 0.0) as u8)?;
+                if let Some(fall) = fall_time(period, channel.duty) {
+                    falls.push((fall, channel.handle.clone()));
+                }
+            }
+        }
+        falls.sort_by_key(|&(fall, _)| fall);
+        for (fall, handle) in falls {
+            if shared.wait_until(Some(start + fall)).is_none() {
+                return Ok(());
+            }
+            handle.set_value(0)?;
+        }
+        start += period;
+        if shared.wait_until(Some(start)).is_none() {
+            return Ok(());
+        }
+        // skip periods missed entirely rather than running them late
+        let now = Instant::now();
+        if now >= start + period {
+            start = now;
+        }
+    }
+}
+
+/// One output of a `SoftPwm`
+///
+/// The duty cycle can be changed at any time and takes effect from
+/// the next period.  Dropping the channel removes it from the
+/// generator and drives its pin low.
+#[derive(Debug)]
+pub struct SoftPwmChannel {
+    id: u64,
+    pin: Pin,
+    shared: Arc<Shared<State>>,
+}
+
+impl SoftPwmChannel {
+    /// Get the pin driven by this channel
+    pub fn get_pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Get the duty cycle of the channel
+    pub fn get_duty_cycle(&self) -> f64 {
+        self.shared
+            .lock()
+            .state
+            .channels
+            .iter()
+            .find(|c| c.id == self.id)
+            .map_or(0.0, |c| c.duty)
+    }
+
+    /// Set the fraction of each period the pin is high, from 0.0 to 1.0
+    pub fn set_duty_cycle(&self, duty: f64) -> Result<()> {
+        let duty = check_duty(duty)?;
+        if let Some(channel) = self
+            .shared
+            .lock()
+            .state
+            .channels
+            .iter_mut()
+            .find(|c| c.id == self.id)
+        {
+            channel.duty = duty;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SoftPwmChannel {
+    fn drop(&mut self) {
+        let channels = &mut self.shared.lock().state.channels;
+        if let Some(pos) = channels.iter().position(|c| c.id == self.id) {
+            let _ = channels.remove(pos).handle.set_value(0);
+        }
+    }
+}
+
+#[test]
+fn fall_time_test() {
+    let period = Duration::from_millis(10);
+    assert_eq!(None, fall_time(period, 0.0));
+    assert_eq!(None, fall_time(period, 1.0));
+    assert_eq!(Some(Duration::from_millis(5)), fall_time(period, 0.5));
+    assert_eq!(Some(Duration::from_micros(2500)), fall_time(period, 0.25));
+}
+
+#[test]
+fn soft_pwm_argument_test() {
+    assert!(SoftPwm::new(0.0).is_err());
+    assert!(SoftPwm::new(-1.0).is_err());
+    assert!(SoftPwm::new(f64::NAN).is_err());
+    // periods too long for a Duration, or too short to be non-zero
+    assert!(SoftPwm::new(1e-300).is_err());
+    assert!(SoftPwm::new(1e300).is_err());
+    assert!(check_duty(1.5).is_err());
+    assert!(check_duty(-0.1).is_err());
+    let pwm = SoftPwm::new(1000.0).unwrap();
+    assert_eq!(Duration::from_millis(1), pwm.period());
+    pwm.stop().unwrap();
+}
@@ -0,0 +1,213 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Edge, Pin, PinPoller, Result};
+
+/// Thresholds for detecting and recovering from an interrupt storm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StormConfig {
+    /// Most interrupts allowed within `window`
+    pub max_events: u32,
+    /// Length of the window interrupts are counted over
+    pub window: Duration,
+    /// How long interrupts stay disabled once a storm is detected
+    pub cooldown: Duration,
+}
+
+/// Something reported by a `StormGuard`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuardEvent {
+    /// An interrupt occurred and the pin had this value
+    Value(u8),
+    /// Too many interrupts occurred; the pin's edge has been set to
+    /// `Edge::NoInterrupt` for the cooldown period
+    StormDetected { events: u32, window: Duration },
+    /// The cooldown has passed and the pin's edge has been restored
+    Rearmed,
+}
+
+/// Counts interrupts over fixed windows
+///
+/// This is the counting logic of a `StormGuard`.  It takes the time
+/// of each interrupt as an argument so that it can be driven by any
+/// clock.
+#[derive(Clone, Debug)]
+pub struct StormDetector {
+    max_events: u32,
+    window: Duration,
+    window_start: Option<Instant>,
+    count: u32,
+}
+
+impl StormDetector {
+    /// Create a detector allowing `max_events` interrupts per `window`
+    pub fn new(max_events: u32, window: Duration) -> StormDetector {
+        StormDetector {
+            max_events,
+            window,
+            window_start: None,
+            count: 0,
+        }
+    }
+
+    /// Get the number of interrupts counted in the current window
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Record an interrupt at `now`, returning `true` if this exceeds the threshold
+    pub fn record(&mut self, now: Instant) -> bool {
+        match self.window_start {
+            Some(start) if now.duration_since(start) < self.window => {}
+            _ => {
+                self.window_start = Some(now);
+                self.count = 0;
+            }
+        }
+        self.count += 1;
+        self.count > self.max_events
+    }
+
+    /// Forget all interrupts counted so far
+    pub fn reset(&mut self) {
+        self.window_start = None;
+        self.count = 0;
+    }
+}
+
+/// A `PinPoller` which disables the pin's interrupt during interrupt storms
+///
+/// A floating input configured for `Edge::BothEdges` can raise tens of
+/// thousands of interrupts per second, keeping a core busy with
+/// `poll`.  A `StormGuard` counts the interrupts it receives; when
+/// more than `max_events` arrive within `window` it sets the pin's
+/// edge to `Edge::NoInterrupt` and reports `GuardEvent::StormDetected`.
+/// Once `cooldown` has passed, the next call to `poll` restores the
+/// edge the pin had when the guard was created and reports
+/// `GuardEvent::Rearmed`.
+///
+/// Re-arming only happens from inside `poll`, so a guard which stops
+/// being polled during the cooldown leaves the interrupt disabled
+/// until it is polled again.  Dropping a disarmed guard restores the
+/// edge immediately.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Edge, GuardEvent, Pin, StormConfig, StormGuard};
+///
+/// let input = Pin::new(23);
+/// input.set_edge(Edge::BothEdges).unwrap();
+/// let config = StormConfig {
+///     max_events: 1000,
+///     window: Duration::from_secs(1),
+///     cooldown: Duration::from_secs(5),
+/// };
+/// let mut guard = StormGuard::new(input.get_poller().unwrap(), config).unwrap();
+/// loop {
+///     match guard.poll(-1).unwrap() {
+///         Some(GuardEvent::Value(value)) => println!("{}", value),
+///         Some(GuardEvent::StormDetected { .. }) => println!("storm, interrupt disabled"),
+///         Some(GuardEvent::Rearmed) => println!("interrupt re-enabled"),
+///         None => {}
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct StormGuard {
+    poller: PinPoller,
+    edge: Edge,
+    config: StormConfig,
+    detector: StormDetector,
+    disarmed_until: Option<Instant>,
+}
+
+impl StormGuard {
+    /// Guard a poller, re-arming with the pin's current edge after a storm
+    pub fn new(poller: PinPoller, config: StormConfig) -> Result<StormGuard> {
+        let edge = poller.get_pin().get_edge()?;
+        Ok(StormGuard {
+            poller,
+            edge,
+            config,
+            detector: StormDetector::new(config.max_events, config.window),
+            disarmed_until: None,
+        })
+    }
+
+    /// Get the pin associated with this StormGuard
+    pub fn get_pin(&self) -> Pin {
+        self.poller.get_pin()
+    }
+
+    /// Determines whether the interrupt is currently disabled by the guard
+    pub fn is_disarmed(&self) -> bool {
+        self.disarmed_until.is_some()
+    }
+
+    /// Block until an interrupt occurs or the guard changes state
+    ///
+    /// Returns `None` if nothing happened within `timeout_ms`
+    /// milliseconds.  A negative timeout waits forever.
+    pub fn poll(&mut self, timeout_ms: isize) -> Result<Option<GuardEvent>> {
+        if let Some(rearm_at) = self.disarmed_until {
+            let now = Instant::now();
+            let wait = rearm_at.saturating_duration_since(now);
+            match u64::try_from(timeout_ms).map(Duration::from_millis) {
+                Ok(timeout) if timeout < wait => {
+                    thread::sleep(timeout);
+                    return Ok(None);
+                }
+                _ => thread::sleep(wait),
+            }
+            self.poller.get_pin().set_edge(self.edge)?;
+            self.detector.reset();
+            self.disarmed_until = None;
+            return Ok(Some(GuardEvent::Rearmed));
+        }
+
+        let value = match self.poller.poll(timeout_ms)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let now = Instant::now();
+        if self.detector.record(now) {
+            self.poller.get_pin().set_edge(Edge::NoInterrupt)?;
+            self.disarmed_until = Some(now + self.config.cooldown);
+            return Ok(Some(GuardEvent::StormDetected {
+                events: self.detector.count(),
+                window: self.config.window,
+            }));
+        }
+        Ok(Some(GuardEvent::Value(value)))
+    }
+}
+
+impl Drop for StormGuard {
+    fn drop(&mut self) {
+        if self.disarmed_until.is_some() {
+            let _ = self.poller.get_pin().set_edge(self.edge);
+        }
+    }
+}
+
+#[test]
+fn storm_detector_test() {
+    let start = Instant::now();
+    let ms = |n| start + Duration::from_millis(n);
+    let mut detector = StormDetector::new(3, Duration::from_millis(100));
+
+    assert!(!detector.record(ms(0)));
+    assert!(!detector.record(ms(10)));
+    assert!(!detector.record(ms(20)));
+    assert!(detector.record(ms(30)));
+    assert_eq!(4, detector.count());
+
+    // a new window starts the count again
+    assert!(!detector.record(ms(100)));
+    assert_eq!(1, detector.count());
+
+    detector.reset();
+    assert_eq!(0, detector.count());
+}
@@ -0,0 +1,325 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::Stream;
+use tokio::time::{Instant, Sleep};
+
+use crate::{Edge, Error, Level, PinEvent, Result};
+
+/// Values which carry the level of a pin
+pub trait AsLevel {
+    fn as_level(&self) -> Level;
+}
+
+impl AsLevel for u8 {
+    fn as_level(&self) -> Level {
+        Level::from(*self)
+    }
+}
+
+impl AsLevel for Level {
+    fn as_level(&self) -> Level {
+        *self
+    }
+}
+
+impl AsLevel for PinEvent {
+    fn as_level(&self) -> Level {
+        self.level
+    }
+}
+
+/// Adapters for streams of pin interrupts
+///
+/// These are implemented for every stream yielding `Result`s, such as
+/// `PinStream`, `PinValueStream` and `PinSetStream`.  Errors from the
+/// underlying stream are always passed through.
+///
+/// This trait is only available when the `async-tokio` crate feature
+/// is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use futures::StreamExt;
+/// use sysfs_gpio::{Edge, Pin, PinStreamExt};
+///
+/// # async fn run() -> sysfs_gpio::Result<()> {
+/// let button = Pin::new(23);
+/// button.set_edge(Edge::BothEdges)?;
+/// let mut presses = button
+///     .get_value_stream()?
+///     .rising_edges()
+///     .throttle(5)
+///     .timeout(Duration::from_secs(30));
+/// while let Some(press) = presses.next().await {
+///     press?;
+///     println!("pressed");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait PinStreamExt<T>: Stream<Item = Result<T>> + Unpin + Sized {
+    /// Yield `Error::Timeout` whenever no item arrives within `window`
+    ///
+    /// The first window starts when the stream is first polled, so the
+    /// adapter can be created outside a tokio runtime.  The stream
+    /// continues after a timeout, with the window starting again.
+    fn timeout(self, window: Duration) -> Timeout<Self> {
+        Timeout {
+            inner: self,
+            window,
+            sleep: None,
+        }
+    }
+
+    /// Pass at most `max_per_second` items in any one second window
+    ///
+    /// Items over the limit are dropped rather than delayed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_per_second` is zero.
+    fn throttle(self, max_per_second: u32) -> Throttle<Self> {
+        assert!(max_per_second > 0, "throttle rate must be greater than 0");
+        Throttle {
+            inner: self,
+            max: max_per_second as usize,
+            passed: VecDeque::with_capacity(max_per_second as usize),
+        }
+    }
+
+    /// Pass only the transitions matching `edge`
+    ///
+    /// A transition is an item whose level differs from the previous
+    /// item's, so repeated values are dropped.  The first item only
+    /// sets the starting level and is never passed, since there is no
+    /// earlier level to compare it with.  `Edge::NoInterrupt` passes no
+    /// items.
+    fn edges(self, edge: Edge) -> EdgeFilter<Self>
+    where
+        T: AsLevel,
+    {
+        EdgeFilter {
+            inner: self,
+            edge,
+            previous: None,
+        }
+    }
+
+    /// Pass only transitions from low to high
+    fn rising_edges(self) -> EdgeFilter<Self>
+    where
+        T: AsLevel,
+    {
+        self.edges(Edge::RisingEdge)
+    }
+
+    /// Pass only transitions from high to low
+    fn falling_edges(self) -> EdgeFilter<Self>
+    where
+        T: AsLevel,
+    {
+        self.edges(Edge::FallingEdge)
+    }
+}
+
+impl<T, S> PinStreamExt<T> for S where S: Stream<Item = Result<T>> + Unpin {}
+
+/// Stream returned by `PinStreamExt::timeout`
+pub struct Timeout<S> {
+    inner: S,
+    window: Duration,
+    /// Created on the first poll, which is within a runtime
+    sleep: Option<std::pin::Pin<Box<Sleep>>>,
+}
+
+impl<T, S> Stream for Timeout<S>
+where
+    S: Stream<Item = Result<T>> + Unpin,
+{
+    type Item = Result<T>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match std::pin::Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(item) => {
+                if let Some(ref mut sleep) = this.sleep {
+                    sleep.as_mut().reset(Instant::now() + this.window);
+                }
+                Poll::Ready(item)
+            }
+            Poll::Pending => {
+                let window = this.window;
+                let sleep = this
+                    .sleep
+                    .get_or_insert_with(|| Box::pin(tokio::time::sleep(window)));
+                futures::ready!(sleep.as_mut().poll(cx));
+                sleep.as_mut().reset(Instant::now() + window);
+                Poll::Ready(Some(Err(Error::Timeout(this.window))))
+            }
+        }
+    }
+}
+
+/// Stream returned by `PinStreamExt::throttle`
+pub struct Throttle<S> {
+    inner: S,
+    max: usize,
+    /// When the items passed within the last second were passed
+    passed: VecDeque<Instant>,
+}
+
+impl<T, S> Stream for Throttle<S>
+where
+    S: Stream<Item = Result<T>> + Unpin,
+{
+    type Item = Result<T>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let item = futures::ready!(std::pin::Pin::new(&mut this.inner).poll_next(cx));
+            if let Some(Ok(_)) = item {
+                let now = Instant::now();
+                while this
+                    .passed
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(1))
+                {
+                    this.passed.pop_front();
+                }
+                if this.passed.len() >= this.max {
+                    continue;
+                }
+                this.passed.push_back(now);
+            }
+            return Poll::Ready(item);
+        }
+    }
+}
+
+/// Stream returned by `PinStreamExt::edges`
+pub struct EdgeFilter<S> {
+    inner: S,
+    edge: Edge,
+    previous: Option<Level>,
+}
+
+impl<T, S> Stream for EdgeFilter<S>
+where
+    T: AsLevel,
+    S: Stream<Item = Result<T>> + Unpin,
+{
+    type Item = Result<T>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let item = futures::ready!(std::pin::Pin::new(&mut this.inner).poll_next(cx));
+            if let Some(Ok(ref value)) = item {
+                let level = value.as_level();
+                let changed = this.previous.is_some_and(|previous| previous != level);
+                this.previous = Some(level);
+                let wanted = match this.edge {
+                    Edge::NoInterrupt => false,
+                    Edge::RisingEdge => level == Level::High,
+                    Edge::FallingEdge => level == Level::Low,
+                    Edge::BothEdges => true,
+                };
+                if !(changed && wanted) {
+                    continue;
+                }
+            }
+            return Poll::Ready(item);
+        }
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn edge_filter_test() {
+    use futures::{stream, StreamExt};
+
+    let values = || stream::iter([0u8, 1, 1, 0, 0, 1, 0].map(Ok));
+    let rising: Vec<u8> = values().rising_edges().map(|v| v.unwrap()).collect().await;
+    assert_eq!(vec![1, 1], rising);
+    let falling: Vec<u8> = values().falling_edges().map(|v| v.unwrap()).collect().await;
+    assert_eq!(vec![0, 0], falling);
+    let both: Vec<u8> = values()
+        .edges(Edge::BothEdges)
+        .map(|v| v.unwrap())
+        .collect()
+        .await;
+    assert_eq!(vec![1, 0, 1, 0], both);
+    // the first item only sets the starting level
+    let first: Vec<u8> = stream::iter([1u8, 1].map(Ok))
+        .rising_edges()
+        .map(|v| v.unwrap())
+        .collect()
+        .await;
+    assert!(first.is_empty());
+    assert_eq!(0, values().edges(Edge::NoInterrupt).count().await);
+}
+
+#[cfg(test)]
+#[tokio::test(start_paused = true)]
+async fn throttle_test() {
+    use futures::StreamExt;
+
+    let (tx, rx) = futures::channel::mpsc::unbounded::<Result<u8>>();
+    tokio::spawn(async move {
+        for value in 0..5 {
+            tx.unbounded_send(Ok(value)).unwrap();
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        tx.unbounded_send(Ok(5)).unwrap();
+    });
+
+    let passed: Vec<u8> = rx.throttle(2).map(|v| v.unwrap()).collect().await;
+    // the burst is cut to two items; a second later the window has moved on
+    assert_eq!(vec![0, 1, 5], passed);
+}
+
+#[cfg(test)]
+#[tokio::test(start_paused = true)]
+async fn timeout_test() {
+    use futures::StreamExt;
+
+    let (tx, rx) = futures::channel::mpsc::unbounded::<Result<u8>>();
+    let mut stream = rx.timeout(Duration::from_millis(100));
+    let start = Instant::now();
+
+    tx.unbounded_send(Ok(1)).unwrap();
+    assert_eq!(1, stream.next().await.unwrap().unwrap());
+    match stream.next().await {
+        Some(Err(Error::Timeout(window))) => assert_eq!(Duration::from_millis(100), window),
+        _ => panic!("expected a timeout"),
+    }
+    assert_eq!(Duration::from_millis(100), start.elapsed());
+
+    // the stream continues after a timeout
+    tx.unbounded_send(Ok(0)).unwrap();
+    assert_eq!(0, stream.next().await.unwrap().unwrap());
+    drop(tx);
+    assert!(stream.next().await.is_none());
+}
+
+#[test]
+fn timeout_outside_runtime_test() {
+    let (_tx, rx) = futures::channel::mpsc::unbounded::<Result<u8>>();
+    // creating the adapter does not need a runtime, only polling it
+    drop(rx.timeout(Duration::from_millis(100)));
+}
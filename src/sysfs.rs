@@ -0,0 +1,85 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+/// Write all of `value` to a sysfs attribute file
+pub(crate) fn write_attribute(path: &Path, value: &str) -> Result<()> {
+    File::create(path)?.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+/// Read the contents of a sysfs attribute file
+pub(crate) fn read_attribute(path: &Path) -> Result<String> {
+    let mut s = String::new();
+    File::open(path)?.read_to_string(&mut s)?;
+    Ok(s)
+}
+
+/// Read a sysfs attribute file holding a single value, such as a number
+pub(crate) fn parse_attribute<T: FromStr>(path: &Path) -> Result<T> {
+    let s = read_attribute(path)?;
+    s.trim().parse().map_err(|_| {
+        Error::Unexpected(format!(
+            "{} file contents {:?}",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            s
+        ))
+    })
+}
+
+/// Write a value to a sysfs attribute file using its `Display` form
+pub(crate) fn write_display<T: Display>(path: &Path, value: T) -> Result<()> {
+    write_attribute(path, &value.to_string())
+}
+
+/// A temporary directory standing in for a sysfs class directory in tests
+#[cfg(test)]
+pub(crate) struct FakeSysfs {
+    pub root: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl FakeSysfs {
+    /// Create an empty directory unique to this process and `name`
+    pub fn new(name: &str) -> FakeSysfs {
+        let root = std::env::temp_dir().join(format!("sysfs-gpio-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        FakeSysfs { root }
+    }
+
+    /// Create a file with `contents` at `path` relative to the root
+    pub fn file(&self, path: &str, contents: &str) {
+        let path = self.root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Read the file at `path` relative to the root
+    pub fn read(&self, path: &str) -> String {
+        std::fs::read_to_string(self.root.join(path)).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl Drop for FakeSysfs {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+#[test]
+fn parse_attribute_test() {
+    let sysfs = FakeSysfs::new("parse-attribute");
+    sysfs.file("npwm", "2\n");
+    sysfs.file("period", "banana\n");
+    assert_eq!(2u32, parse_attribute(&sysfs.root.join("npwm")).unwrap());
+    match parse_attribute::<u32>(&sysfs.root.join("period")) {
+        Err(Error::Unexpected(msg)) => assert!(msg.starts_with("period file contents")),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
@@ -0,0 +1,389 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::FileExt;
+use std::time::{Duration, Instant};
+
+use io_uring::{opcode, squeue, types, IoUring};
+
+use crate::{Error, Level, Pin, PinEvent, Result};
+
+/// Tags stored in the top byte of each submission's user data
+const READ: u64 = 1 << 56;
+const WRITE: u64 = 2 << 56;
+const POLL: u64 = 3 << 56;
+const INDEX_MASK: u64 = (1 << 56) - 1;
+
+/// The values of many pins accessed through a single io_uring
+///
+/// `Pin::get_value` and `Pin::set_value` open, access and close the
+/// value file on each call, and each `PinPoller` has its own epoll
+/// instance.  On boards with hundreds of pins this adds up.  A
+/// `UringPins` keeps the value files of a set of pins open and
+/// submits the reads, writes and interrupt waits for all of them in
+/// batches through one io_uring, so accessing any number of pins costs
+/// a single system call.
+///
+/// Values have the same meaning as with `Pin`: 0 or 1 subject to the
+/// "active_low" setting of each pin.  Pins are addressed by their
+/// index in the list the `UringPins` was created with.
+///
+/// Waiting for interrupts requires Linux 5.11 or later.  This type is
+/// only available when the `io-uring` crate feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sysfs_gpio::{Edge, Pin, UringPins};
+///
+/// let inputs: Vec<Pin> = (100..200).map(Pin::new).collect();
+/// for pin in &inputs {
+///     pin.set_edge(Edge::BothEdges).unwrap();
+/// }
+/// let mut pins = UringPins::new(&inputs).unwrap();
+/// println!("{:?}", pins.read_values().unwrap());
+/// for event in pins.wait(Some(Duration::from_secs(1))).unwrap() {
+///     println!("{} -> {:?}", event.pin.get_pin(), event.level);
+/// }
+/// ```
+pub struct UringPins {
+    ring: IoUring,
+    pins: Vec<Pin>,
+    files: Vec<File>,
+    /// Read buffers, which the kernel writes to while reads are in flight
+    buffers: Vec<[u8; 2]>,
+    /// Reads and writes queued or submitted but not yet completed
+    in_flight: usize,
+    armed: Vec<bool>,
+    fired: Vec<usize>,
+    /// The first failed interrupt wait, reported by the next `wait`
+    poll_error: Option<io::Error>,
+}
+
+impl UringPins {
+    /// Open the value files of a list of exported pins
+    pub fn new(pins: &[Pin]) -> Result<UringPins> {
+        if pins.len() as u64 > INDEX_MASK {
+            return Err(Error::InvalidArgument("too many pins".to_owned()));
+        }
+        let files = pins
+            .iter()
+            .map(|pin| {
+                OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(format!("/sys/class/gpio/gpio{}/value", pin.get_pin()))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        // acknowledge any interrupts which are already pending
+        let mut buf = [0u8; 2];
+        for file in &files {
+            file.read_at(&mut buf, 0)?;
+        }
+        let entries = (pins.len() as u32).clamp(8, 4096).next_power_of_two();
+        Ok(UringPins {
+            ring: IoUring::new(entries)?,
+            pins: pins.to_vec(),
+            files,
+            buffers: vec![[0u8; 2]; pins.len()],
+            in_flight: 0,
+            armed: vec![false; pins.len()],
+            fired: Vec::new(),
+            poll_error: None,
+        })
+    }
+
+    /// Get the pins, in the order they are indexed
+    pub fn pins(&self) -> &[Pin] {
+        &self.pins
+    }
+
+    /// Read the values of all pins
+    pub fn read_values(&mut self) -> Result<Vec<u8>> {
+        let indices: Vec<usize> = (0..self.pins.len()).collect();
+        self.read_batch(&indices)
+    }
+
+    /// Write the value of each `(index, value)` pair in a single batch
+    ///
+    /// A 0 value will set the pin low and any other value will set
+    /// the pin high.
+    pub fn write_values(&mut self, writes: &[(usize, u8)]) -> Result<()> {
+        for &(index, _) in writes {
+            self.check_index(index)?;
+        }
+        self.settle()?;
+        let result = self.write_batch(writes);
+        self.settle_after(result)
+    }
+
+    fn write_batch(&mut self, writes: &[(usize, u8)]) -> Result<()> {
+        for &(index, value) in writes {
+            let buf: &'static [u8] = match value {
+                0 => b"0",
+                _ => b"1",
+            };
+            let entry = opcode::Write::new(
+                types::Fd(self.files[index].as_raw_fd()),
+                buf.as_ptr(),
+                buf.len() as u32,
+            )
+            .offset(0)
+            .build()
+            .user_data(WRITE | index as u64);
+            self.push(&entry)?;
+        }
+        let mut remaining = writes.len();
+        let mut first_error = None;
+        while remaining > 0 {
+            for (user_data, res) in self.submit_and_reap(1, None)? {
+                if user_data & !INDEX_MASK == WRITE {
+                    remaining -= 1;
+                    if res < 0 && first_error.is_none() {
+                        first_error = Some(io::Error::from_raw_os_error(-res));
+                    }
+                }
+            }
+        }
+        match first_error {
+            Some(err) => Err(err.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait for interrupts on any of the pins
+    ///
+    /// Returns an event for every pin which raised an interrupt, with
+    /// the value read after the interrupt, or an empty list if none
+    /// occurred within `timeout`.  A `timeout` of `None` waits forever,
+    /// which is an error if there are no pins to wait for.  Interrupts
+    /// which occur between calls are not lost.
+    pub fn wait(&mut self, timeout: Option<Duration>) -> Result<Vec<PinEvent>> {
+        if self.pins.is_empty() && timeout.is_none() {
+            return Err(Error::InvalidArgument(
+                "waiting forever for interrupts on no pins".to_owned(),
+            ));
+        }
+        for index in 0..self.pins.len() {
+            if !self.armed[index] {
+                let entry = opcode::PollAdd::new(
+                    types::Fd(self.files[index].as_raw_fd()),
+                    nix::libc::POLLPRI as u32,
+                )
+                .build()
+                .user_data(POLL | index as u64);
+                self.push(&entry)?;
+                self.armed[index] = true;
+            }
+        }
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        while self.fired.is_empty() && self.poll_error.is_none() {
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            // returns early on EINTR, or on completion of a read or
+            // write left in flight by a failed batch
+            self.submit_and_reap(1, remaining)?;
+            if remaining.is_some_and(|r| r.is_zero()) {
+                break;
+            }
+        }
+        if let Some(err) = self.poll_error.take() {
+            return Err(err.into());
+        }
+
+        let mut fired = std::mem::take(&mut self.fired);
+        fired.sort_unstable();
+        fired.dedup();
+        let values = self.read_batch(&fired)?;
+        let timestamp = Instant::now();
+        Ok(fired
+            .into_iter()
+            .zip(values)
+            .map(|(index, value)| PinEvent {
+                pin: self.pins[index],
+                level: Level::from(value),
+                timestamp,
+            })
+            .collect())
+    }
+
+    fn check_index(&self, index: usize) -> Result<()> {
+        match index < self.pins.len() {
+            true => Ok(()),
+            false => Err(Error::InvalidArgument(format!(
+                "pin index {} out of range",
+                index
+            ))),
+        }
+    }
+
+    /// Wait for every read and write still in flight to complete
+    ///
+    /// Batches settle before they start, so that the completions they
+    /// count are their own and no read buffer is reused while the
+    /// kernel may still write to it.
+    fn settle(&mut self) -> Result<()> {
+        while self.in_flight > 0 {
+            self.submit_and_reap(1, None)?;
+        }
+        Ok(())
+    }
+
+    /// Settle after a batch which may have failed with operations in flight
+    fn settle_after<T>(&mut self, result: Result<T>) -> Result<T> {
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                // the batch's error is more useful than any from settling;
+                // if settling fails the next batch settles again first
+                let _ = self.settle();
+                Err(err)
+            }
+        }
+    }
+
+    /// Read the values of the pins at `indices` in a single batch
+    fn read_batch(&mut self, indices: &[usize]) -> Result<Vec<u8>> {
+        self.settle()?;
+        let result = self.submit_reads(indices);
+        self.settle_after(result)
+    }
+
+    fn submit_reads(&mut self, indices: &[usize]) -> Result<Vec<u8>> {
+        for &index in indices {
+            let buf = self.buffers[index].as_mut_ptr();
+            let entry = opcode::Read::new(types::Fd(self.files[index].as_raw_fd()), buf, 2)
+                .offset(0)
+                .build()
+                .user_data(READ | index as u64);
+            self.push(&entry)?;
+        }
+        let mut results: Vec<Option<i32>> = vec![None; self.pins.len()];
+        let mut remaining = indices.len();
+        while remaining > 0 {
+            for (user_data, res) in self.submit_and_reap(1, None)? {
+                if user_data & !INDEX_MASK == READ {
+                    results[(user_data & INDEX_MASK) as usize] = Some(res);
+                    remaining -= 1;
+                }
+            }
+        }
+        indices
+            .iter()
+            .map(|&index| match results[index] {
+                Some(res) if res < 0 => Err(io::Error::from_raw_os_error(-res).into()),
+                Some(0) | None => Err(Error::Unexpected(format!(
+                    "short read of value file of pin {}",
+                    self.pins[index].get_pin()
+                ))),
+                Some(_) => match self.buffers[index][0] {
+                    b'0' => Ok(0),
+                    b'1' => Ok(1),
+                    other => Err(Error::Unexpected(format!(
+                        "value file contents {:?}",
+                        other as char
+                    ))),
+                },
+            })
+            .collect()
+    }
+
+    /// Queue a submission, flushing the queue to the kernel if it is full
+    fn push(&mut self, entry: &squeue::Entry) -> Result<()> {
+        loop {
+            // SAFETY: queued reads refer to heap buffers owned by self,
+            // which are not reused until the read completes (`settle`)
+            // and are leaked on drop if it never does; writes use static
+            // buffers
+            if unsafe { self.ring.submission().push(entry) }.is_ok() {
+                if entry.get_user_data() & !INDEX_MASK != POLL {
+                    self.in_flight += 1;
+                }
+                return Ok(());
+            }
+            self.ring.submit()?;
+        }
+    }
+
+    /// Submit queued entries, wait for `want` completions and collect them
+    ///
+    /// Poll completions are recorded as fired pins; the user data and
+    /// result of every completion are returned.
+    fn submit_and_reap(
+        &mut self,
+        want: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(u64, i32)>> {
+        let submitted = match timeout {
+            Some(timeout) => {
+                let ts = types::Timespec::from(timeout);
+                let args = types::SubmitArgs::new().timespec(&ts);
+                self.ring.submitter().submit_with_args(want, &args)
+            }
+            None => self.ring.submit_and_wait(want),
+        };
+        match submitted {
+            Ok(_) => {}
+            Err(ref err) if err.raw_os_error() == Some(nix::libc::ETIME) => {}
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+        let completions: Vec<(u64, i32)> = self
+            .ring
+            .completion()
+            .map(|cqe| (cqe.user_data(), cqe.result()))
+            .collect();
+        for &(user_data, res) in &completions {
+            if user_data & !INDEX_MASK == POLL {
+                let index = (user_data & INDEX_MASK) as usize;
+                self.armed[index] = false;
+                if res < 0 {
+                    self.poll_error
+                        .get_or_insert_with(|| io::Error::from_raw_os_error(-res));
+                } else {
+                    self.fired.push(index);
+                }
+            } else {
+                self.in_flight -= 1;
+            }
+        }
+        Ok(completions)
+    }
+}
+
+impl fmt::Debug for UringPins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UringPins")
+            .field("pins", &self.pins)
+            .field("in_flight", &self.in_flight)
+            .field("armed", &self.armed)
+            .field("fired", &self.fired)
+            .field("poll_error", &self.poll_error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for UringPins {
+    fn drop(&mut self) {
+        // dropping the ring does not wait for operations in flight, so
+        // the kernel could still write to the buffers after they are freed
+        if self.settle().is_err() {
+            std::mem::forget(std::mem::take(&mut self.buffers));
+        }
+    }
+}
+
+#[test]
+fn wait_without_pins_test() {
+    let mut pins = UringPins::new(&[]).unwrap();
+    assert!(matches!(pins.wait(None), Err(Error::InvalidArgument(_))));
+    let start = Instant::now();
+    assert!(pins
+        .wait(Some(Duration::from_millis(10)))
+        .unwrap()
+        .is_empty());
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert!(pins.read_values().unwrap().is_empty());
+    assert!(format!("{:?}", pins).starts_with("UringPins { pins: []"));
+}
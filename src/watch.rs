@@ -0,0 +1,54 @@
+use futures::{Stream, StreamExt};
+use tokio::sync::watch;
+
+use crate::{Level, Result};
+
+/// Publish the changes in a stream of pin values on a watch channel
+///
+/// Values equal to the current level are not published, which filters
+/// out the duplicates a `PinValueStream` can produce.  Returns when
+/// the stream ends or fails, or when all receivers have been dropped.
+pub(crate) async fn publish_levels<S>(mut stream: S, tx: watch::Sender<Level>)
+where
+    S: Stream<Item = Result<u8>> + Unpin,
+{
+    loop {
+        tokio::select! {
+            _ = tx.closed() => return,
+            value = stream.next() => match value {
+                Some(Ok(value)) => {
+                    let level = Level::from(value);
+                    tx.send_if_modified(|current| {
+                        let changed = *current != level;
+                        *current = level;
+                        changed
+                    });
+                }
+                _ => return,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn publish_levels_test() {
+    let (values, stream) = futures::channel::mpsc::unbounded::<Result<u8>>();
+    let (tx, mut rx) = watch::channel(Level::Low);
+    let task = tokio::spawn(publish_levels(stream, tx));
+
+    // duplicates of the current level are not published
+    values.unbounded_send(Ok(0)).unwrap();
+    values.unbounded_send(Ok(1)).unwrap();
+    rx.changed().await.unwrap();
+    assert_eq!(Level::High, *rx.borrow_and_update());
+    values.unbounded_send(Ok(1)).unwrap();
+    values.unbounded_send(Ok(0)).unwrap();
+    rx.changed().await.unwrap();
+    assert_eq!(Level::Low, *rx.borrow_and_update());
+
+    // the task ends with the stream, closing the channel
+    drop(values);
+    task.await.unwrap();
+    assert!(rx.changed().await.is_err());
+}
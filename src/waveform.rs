@@ -0,0 +1,403 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::worker::Worker;
+use crate::{Error, Level, Pin, PinHandle, Result};
+
+/// One step of a `Waveform`: hold `level` for `duration`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub level: Level,
+    pub duration: Duration,
+}
+
+/// A sequence of output levels, each held for a fixed time
+///
+/// A waveform can be built in code or parsed from a compact string.
+/// The string form is a list of segments separated by whitespace or
+/// commas, where each segment is a level (`H` or `L`) followed by
+/// a duration with a unit of `ns`, `us`, `ms` or `s`.
+/// Segments can be grouped in parentheses and repeated by following
+/// the group with `x` and a count:
+///
+/// ```
+/// use std::time::Duration;
+/// use sysfs_gpio::{Level, Waveform};
+///
+/// let parsed: Waveform = "H10ms L2ms (H1ms L1ms)x3".parse().unwrap();
+/// let built = Waveform::new()
+///     .then(Level::High, Duration::from_millis(10))
+///     .then(Level::Low, Duration::from_millis(2))
+///     .then_repeat(
+///         &Waveform::new()
+///             .then(Level::High, Duration::from_millis(1))
+///             .then(Level::Low, Duration::from_millis(1)),
+///         3,
+///     );
+/// assert_eq!(parsed, built);
+/// assert_eq!(Duration::from_millis(18), parsed.duration());
+/// ```
+///
+/// Repeated groups are expanded, so a waveform holds every segment
+/// it will play.  To keep that bounded, a waveform may have at most
+/// 2^20 segments, and parsing a string which would exceed this, which
+/// nests groups more than 64 deep, or whose total duration does not fit
+/// in a `Duration`, fails with `Error::InvalidArgument`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Waveform {
+    segments: Vec<Segment>,
+}
+
+/// The maximum number of segments in a `Waveform`
+const MAX_SEGMENTS: usize = 1 << 20;
+
+/// The maximum nesting of parenthesised groups in a parsed `Waveform`
+const MAX_DEPTH: usize = 64;
+
+/// Determines whether `count` more segments fit in a waveform of `len` segments
+fn fits(len: usize, count: usize) -> bool {
+    len.checked_add(count)
+        .is_some_and(|total| total <= MAX_SEGMENTS)
+}
+
+impl Waveform {
+    /// Create an empty waveform
+    pub fn new() -> Waveform {
+        Waveform::default()
+    }
+
+    /// Append a segment holding `level` for `duration`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the waveform already has the maximum number of segments.
+    pub fn then(mut self, level: Level, duration: Duration) -> Waveform {
+        assert!(fits(self.segments.len(), 1), "too many waveform segments");
+        self.segments.push(Segment { level, duration });
+        self
+    }
+
+    /// Append `count` copies of another waveform
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result would exceed the maximum number of segments.
+    pub fn then_repeat(mut self, other: &Waveform, count: u32) -> Waveform {
+        let added = other.segments.len().checked_mul(count as usize);
+        assert!(
+            added.is_some_and(|added| fits(self.segments.len(), added)),
+            "too many waveform segments"
+        );
+        for _ in 0..count {
+            self.segments.extend_from_slice(&other.segments);
+        }
+        self
+    }
+
+    /// Get the segments of the waveform in the order they are played
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Get the time taken to play the waveform once
+    ///
+    /// This saturates at `Duration::MAX` for a waveform built in code
+    /// whose total duration overflows.
+    pub fn duration(&self) -> Duration {
+        self.checked_duration().unwrap_or(Duration::MAX)
+    }
+
+    fn checked_duration(&self) -> Option<Duration> {
+        self.segments
+            .iter()
+            .try_fold(Duration::ZERO, |total, s| total.checked_add(s.duration))
+    }
+
+    /// Play the waveform once on a background thread, driving all of `pins` together
+    ///
+    /// The pins must already be exported and configured as outputs.
+    /// Their value files are opened before this returns, so errors
+    /// opening them are reported here rather than by the player.
+    pub fn play(&self, pins: &[Pin]) -> Result<WaveformPlayer> {
+        self.start(pins, false)
+    }
+
+    /// Play the waveform repeatedly until the player is stopped
+    ///
+    /// This is `play` with looping enabled from the start; looping
+    /// can be turned off later with `WaveformPlayer::set_looping`.
+    pub fn play_looping(&self, pins: &[Pin]) -> Result<WaveformPlayer> {
+        self.start(pins, true)
+    }
+
+    fn start(&self, pins: &[Pin], looping: bool) -> Result<WaveformPlayer> {
+        let handles = pins
+            .iter()
+            .map(Pin::get_handle)
+            .collect::<Result<Vec<_>>>()?;
+        WaveformPlayer::start(self.clone(), handles, looping)
+    }
+}
+
+impl FromStr for Waveform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Waveform> {
+        let mut parser = Parser {
+            s,
+            pos: 0,
+            depth: 0,
+        };
+        let waveform = parser.sequence()?;
+        match parser.peek() {
+            None if waveform.checked_duration().is_none() => {
+                Err(parser.error("total duration too long"))
+            }
+            None => Ok(waveform),
+            Some(_) => Err(parser.error("unexpected ')'")),
+        }
+    }
+}
+
+/// Recursive descent parser for the string form of a `Waveform`
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Skip separators and return the next character without consuming it
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.s[self.pos..];
+        let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        self.pos += rest.len() - trimmed.len();
+        trimmed.chars().next()
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::InvalidArgument(format!(
+            "waveform {:?}: {} at offset {}",
+            self.s, msg, self.pos
+        ))
+    }
+
+    /// Consume a run of characters matching `pred` from the current position
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &str {
+        let rest = &self.s[self.pos..];
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn number(&mut self) -> Result<u64> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        match digits.parse() {
+            Ok(n) => Ok(n),
+            Err(_) => Err(self.error("expected a number")),
+        }
+    }
+
+    fn sequence(&mut self) -> Result<Waveform> {
+        let mut waveform = Waveform::new();
+        loop {
+            match self.peek() {
+                None | Some(')') => return Ok(waveform),
+                Some('(') => {
+                    if self.depth == MAX_DEPTH {
+                        return Err(self.error("groups nested too deeply"));
+                    }
+                    self.pos += 1;
+                    self.depth += 1;
+                    let group = self.sequence()?;
+                    self.depth -= 1;
+                    if self.peek() != Some(')') {
+                        return Err(self.error("expected ')'"));
+                    }
+                    self.pos += 1;
+                    let count = match self.s[self.pos..].chars().next() {
+                        Some('x') | Some('X') => {
+                            self.pos += 1;
+                            u32::try_from(self.number()?)
+                                .map_err(|_| self.error("repeat count too large"))?
+                        }
+                        _ => 1,
+                    };
+                    let added = group.segments.len().checked_mul(count as usize);
+                    if !added.is_some_and(|added| fits(waveform.segments.len(), added)) {
+                        return Err(self.error("too many segments"));
+                    }
+                    waveform = waveform.then_repeat(&group, count);
+                }
+                Some(c) => {
+                    let level = match c {
+                        'H' | 'h' => Level::High,
+                        'L' | 'l' => Level::Low,
+                        _ => return Err(self.error("expected a level")),
+                    };
+                    self.pos += 1;
+                    let duration = self.duration()?;
+                    if !fits(waveform.segments.len(), 1) {
+                        return Err(self.error("too many segments"));
+                    }
+                    waveform = waveform.then(level, duration);
+                }
+            }
+        }
+    }
+
+    fn duration(&mut self) -> Result<Duration> {
+        let n = self.number()?;
+        let duration = match self.take_while(|c| c.is_ascii_alphabetic()) {
+            "ns" => Duration::from_nanos(n),
+            "us" => Duration::from_micros(n),
+            "ms" => Duration::from_millis(n),
+            "s" => Duration::from_secs(n),
+            _ => return Err(self.error("expected a unit of ns, us, ms or s")),
+        };
+        Ok(duration)
+    }
+}
+
+/// Handle on a waveform playing on a background thread
+///
+/// The player is started by `Waveform::play` or
+/// `Waveform::play_looping`.  Segments are timed
+/// against a running deadline, so the time taken to write the pins
+/// does not accumulate over a long waveform.  Pausing holds the pins
+/// at their current level and resumes with the remainder of the
+/// current segment.
+///
+/// Stopping the player, or dropping the handle, leaves the pins low.
+/// A player which plays to the end leaves the pins at the level of
+/// the last segment.
+#[derive(Debug)]
+pub struct WaveformPlayer {
+    /// The worker's state is whether the waveform loops
+    worker: Worker<bool>,
+}
+
+impl WaveformPlayer {
+    fn start(waveform: Waveform, handles: Vec<PinHandle>, looping: bool) -> Result<WaveformPlayer> {
+        let worker = Worker::spawn("sysfs-gpio-waveform", looping, move |player| {
+            let set_all = |level: Level| -> Result<()> {
+                handles.iter().try_for_each(|h| h.set_value(level.into()))
+            };
+            let mut deadline = Instant::now();
+            loop {
+                for segment in &waveform.segments {
+                    set_all(segment.level)?;
+                    // a deadline too far to represent is never reached
+                    match player.wait_until(deadline.checked_add(segment.duration)) {
+                        Some(next) => deadline = next,
+                        None => return set_all(Level::Low),
+                    }
+                }
+                if !player.lock().state || waveform.segments.is_empty() {
+                    return Ok(());
+                }
+            }
+        })?;
+        Ok(WaveformPlayer { worker })
+    }
+
+    /// Pause playback, holding the pins at their current level
+    pub fn pause(&self) {
+        self.worker.shared().update(|c| c.paused = true);
+    }
+
+    /// Resume playback after `pause`
+    pub fn resume(&self) {
+        self.worker.shared().update(|c| c.paused = false);
+    }
+
+    /// Determines whether playback is paused
+    pub fn is_paused(&self) -> bool {
+        self.worker.shared().lock().paused
+    }
+
+    /// Set whether the waveform restarts from the beginning when it ends
+    ///
+    /// Clearing this while the waveform is playing lets the current
+    /// pass finish and then stops.  To loop from the start, use
+    /// `Waveform::play_looping`.
+    pub fn set_looping(&self, looping: bool) {
+        self.worker.shared().update(|c| c.state = looping);
+    }
+
+    /// Stop playback, leaving the pins low
+    pub fn stop(mut self) -> Result<()> {
+        self.worker.stop()
+    }
+
+    /// Wait for the waveform to finish playing
+    ///
+    /// This never returns while looping is enabled, unless a write
+    /// to one of the pins fails.
+    pub fn join(mut self) -> Result<()> {
+        self.worker.join()
+    }
+
+    /// Determines whether playback has completed
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+}
+
+#[test]
+fn parse_waveform_test() {
+    let ms = Duration::from_millis;
+    let waveform: Waveform = "h1s, L250us (H3ms (L1ms)x2)X2 H40ns".parse().unwrap();
+    let levels: Vec<_> = waveform.segments().iter().map(|s| s.level).collect();
+    assert_eq!(
+        vec![
+            Level::High,
+            Level::Low,
+            Level::High,
+            Level::Low,
+            Level::Low,
+            Level::High,
+            Level::Low,
+            Level::Low,
+            Level::High
+        ],
+        levels
+    );
+    assert_eq!(
+        Duration::from_secs(1) + Duration::from_micros(250) + ms(10) + Duration::from_nanos(40),
+        waveform.duration()
+    );
+    assert_eq!(Waveform::new(), "".parse().unwrap());
+    assert_eq!(Waveform::new(), "(H1ms)x0".parse().unwrap());
+    let nested = format!("{}H1ms{}", "(".repeat(64), ")".repeat(64));
+    assert_eq!(
+        Duration::from_millis(1),
+        nested.parse::<Waveform>().unwrap().duration()
+    );
+}
+
+#[test]
+fn parse_waveform_error_test() {
+    for bad in [
+        "H",
+        "H10",
+        "1 10ms",
+        "H10m",
+        "X10ms",
+        "(H1ms",
+        "H1ms)",
+        "(H1ms)x",
+        "(H1ms)x4000000000",
+        "(H1ms)x2000000",
+        "((H1ms)x100000)x100000",
+        "H18446744073709551615s H1s",
+        "(".repeat(200_000).as_str(),
+        format!("{}H1ms{}", "(".repeat(65), ")".repeat(65)).as_str(),
+    ] {
+        assert!(
+            matches!(bad.parse::<Waveform>(), Err(Error::InvalidArgument(_))),
+            "{:?} should not parse",
+            bad
+        );
+    }
+}
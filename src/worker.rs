@@ -0,0 +1,176 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{Error, Result};
+
+/// Flags shared by a background worker and its handle, with worker-specific state
+#[derive(Debug)]
+pub(crate) struct Control<T> {
+    pub stop: bool,
+    pub paused: bool,
+    pub state: T,
+}
+
+/// The lock and condition variable shared by a worker and its handle
+#[derive(Debug)]
+pub(crate) struct Shared<T> {
+    control: Mutex<Control<T>>,
+    changed: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn new(state: T) -> Shared<T> {
+        Shared {
+            control: Mutex::new(Control {
+                stop: false,
+                paused: false,
+                state,
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Control<T>> {
+        // nothing holding the lock runs user code, so a poisoned lock
+        // still holds consistent state
+        self.control.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Change the shared state and wake the worker
+    pub fn update(&self, f: impl FnOnce(&mut Control<T>)) {
+        f(&mut self.lock());
+        self.changed.notify_all();
+    }
+
+    /// Wait until `deadline`, pushing it back by any time spent paused
+    ///
+    /// Returns the new deadline, or `None` if the worker was stopped.
+    /// A `deadline` of `None` waits until the worker is stopped.
+    pub fn wait_until(&self, mut deadline: Option<Instant>) -> Option<Instant> {
+        let mut control = self.lock();
+        loop {
+            if control.stop {
+                return None;
+            }
+            let now = Instant::now();
+            if control.paused {
+                control = self
+                    .changed
+                    .wait_while(control, |c| c.paused && !c.stop)
+                    .unwrap_or_else(|e| e.into_inner());
+                deadline = deadline.and_then(|d| d.checked_add(now.elapsed()));
+                continue;
+            }
+            control = match deadline {
+                Some(deadline) if now >= deadline => return Some(deadline),
+                Some(deadline) => {
+                    self.changed
+                        .wait_timeout(control, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(control)
+                    .unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+    }
+
+    /// Wait for `duration` from now, returning false if the worker was stopped
+    pub fn sleep(&self, duration: Duration) -> bool {
+        self.wait_until(Instant::now().checked_add(duration))
+            .is_some()
+    }
+}
+
+/// A background thread which can be stopped and joined from its handle
+///
+/// Dropping the worker stops it and waits for the thread to exit.
+#[derive(Debug)]
+pub(crate) struct Worker<T> {
+    name: &'static str,
+    shared: Arc<Shared<T>>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl<T: Send + 'static> Worker<T> {
+    /// Start a thread running `f` with access to the shared state
+    pub fn spawn<F>(name: &'static str, state: T, f: F) -> Result<Worker<T>>
+    where
+        F: FnOnce(&Shared<T>) -> Result<()> + Send + 'static,
+    {
+        let shared = Arc::new(Shared::new(state));
+        let worker = shared.clone();
+        let thread = thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || f(&worker))?;
+        Ok(Worker {
+            name,
+            shared,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl<T> Worker<T> {
+    pub fn shared(&self) -> &Arc<Shared<T>> {
+        &self.shared
+    }
+
+    /// Ask the thread to stop and wait for it, returning its result
+    pub fn stop(&mut self) -> Result<()> {
+        self.shared.update(|c| c.stop = true);
+        self.join()
+    }
+
+    /// Wait for the thread to exit, returning its result
+    pub fn join(&mut self) -> Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .map_err(|_| Error::Unexpected(format!("{} thread panicked", self.name)))?,
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(|t| t.is_finished())
+    }
+}
+
+impl<T> Drop for Worker<T> {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[test]
+fn wait_until_test() {
+    let shared = Shared::new(());
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(5);
+    assert_eq!(Some(deadline), shared.wait_until(Some(deadline)));
+    assert!(start.elapsed() >= Duration::from_millis(5));
+    shared.update(|c| c.stop = true);
+    assert_eq!(
+        None,
+        shared.wait_until(Some(start + Duration::from_secs(10)))
+    );
+    assert_eq!(None, shared.wait_until(None));
+}
+
+#[test]
+fn worker_stop_test() {
+    let mut worker = Worker::spawn("test", 0u32, |shared| {
+        while shared.sleep(Duration::from_millis(1)) {
+            shared.lock().state += 1;
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert!(!worker.is_finished());
+    worker.stop().unwrap();
+    assert!(worker.is_finished());
+}